@@ -1,15 +1,25 @@
 use clap::builder::ValueParser;
 use clap::{crate_version, value_parser, Arg, ArgAction, Command};
 
+use crate::command::OutputFormat;
 use crate::command::PathKind;
 use crate::command::Question;
 use crate::component::arg_into_component;
+use crate::valid::Platform;
 
 pub fn build() -> Command {
     Command::new("pathmut")
         .version(crate_version!())
         .about("Mutate path strings")
-        .args([normalize_arg(), parse_as_unix_arg(), parse_as_win_arg()])
+        .args([
+            normalize_arg(),
+            parse_as_unix_arg(),
+            parse_as_win_arg(),
+            parse_as_uri_arg(),
+            parse_as_git_arg(),
+            null_arg(),
+            output_arg(),
+        ])
         .subcommands([
             get_command(),
             remove_command(),
@@ -19,8 +29,16 @@ pub fn build() -> Command {
             is_command(),
             normalize_command(),
             convert_command(),
-            //info_command(),
+            info_command(),
             depth_command(),
+            format_command(),
+            join_command(),
+            relative_command(),
+            common_command(),
+            strip_prefix_command(),
+            rebase_command(),
+            sanitize_command(),
+            resolve_command(),
         ])
         .dont_delimit_trailing_values(true)
         .arg_required_else_help(true)
@@ -38,6 +56,25 @@ fn normalize_arg() -> Arg {
         .help("Normalize the path first")
 }
 
+fn null_arg() -> Arg {
+    Arg::new("null")
+        .global(true)
+        .short('0')
+        .long("null")
+        .action(ArgAction::SetTrue)
+        .help("Separate paths from stdin and results with NUL, not newline")
+}
+
+fn output_arg() -> Arg {
+    Arg::new("output")
+        .global(true)
+        .long("output")
+        .value_name("FORMAT")
+        .default_value("text")
+        .value_parser(value_parser!(OutputFormat))
+        .help("Print results as plain text, a JSON array, or JSON lines")
+}
+
 fn parse_as_win_arg() -> Arg {
     Arg::new("as-windows")
         .global(true)
@@ -58,6 +95,24 @@ fn parse_as_unix_arg() -> Arg {
         .help("Parse paths as unix paths")
 }
 
+fn parse_as_uri_arg() -> Arg {
+    Arg::new("as-uri")
+        .global(true)
+        .long("as-uri")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["as-unix", "as-windows"])
+        .help("Parse paths as URIs, for get, set, delete, replace, has and normalize")
+}
+
+fn parse_as_git_arg() -> Arg {
+    Arg::new("as-git")
+        .global(true)
+        .long("as-git")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["as-unix", "as-windows", "as-uri"])
+        .help("Parse paths as git remotes, for get, set, delete, replace, has and convert")
+}
+
 fn components_help_section() -> &'static str {
     "\x1B[4;1mComponents:\x1B[0m\n\
     \x20 \x1B[1mext\x1B[0m      File extension\n\
@@ -66,7 +121,19 @@ fn components_help_section() -> &'static str {
     \x20 \x1B[1mname\x1B[0m     File name\n\
     \x20 \x1B[1mparent\x1B[0m   Parent of the file or directory\n\
     \x20 \x1B[1mdisk\x1B[0m     Disk of a windows path\n\
-    \x20 \x1B[1;3mn\x1B[0m        Ordinal of the nth component\n"
+    \x20 \x1B[1mexts\x1B[0m     Every extension after the prefix\n\
+    \x20 \x1B[1mext:\x1B[3mn\x1B[0m    Ordinal of the nth extension\n\
+    \x20 \x1B[1;3mn\x1B[0m        Ordinal of the nth component\n\
+    \x20 \x1B[1;3mm\x1B[0;1m..\x1B[3mn\x1B[0m     Components from the mth up to the nth, like 1..3, ..-1, or 2..\n\
+    \n\
+    With --as-uri, the scheme, user, password, host, port, route, query and fragment\n\
+    of a URI are components too, and the path components act on the route.  Query\n\
+    parameters are components like query:page, or query:page:n for the nth repeat,\n\
+    and keys lists every parameter.\n\n\
+    With --as-git, the scheme, user, host, port and route of a git remote are\n\
+    components, where the route is the path of the repository on the host and the\n\
+    path components act on it.  The owner is every directory before the repository,\n\
+    and the repo is its name without .git.\n"
 }
 
 fn questions_help_section() -> &'static str {
@@ -75,7 +142,11 @@ fn questions_help_section() -> &'static str {
     \x20 \x1B[1mrelative\x1B[0m\n\
     \x20 \x1B[1munix\x1B[0m\n\
     \x20 \x1B[1mwindows\x1B[0m\n\
-    \x20 \x1B[1mnormalized\x1B[0m\n"
+    \x20 \x1B[1mnormalized\x1B[0m\n\
+    \x20 \x1B[1mstarts-with\x1B[0m  Paths begin with the components of the first path\n\
+    \x20 \x1B[1mends-with\x1B[0m    Paths end with the components of the first path\n\
+    \x20 \x1B[1mcontains\x1B[0m     Paths contain the components of the first path\n\
+    \x20 \x1B[1mvalid\x1B[0m        Paths could exist on the target platform\n"
 }
 
 fn component_arg() -> Arg {
@@ -84,15 +155,22 @@ fn component_arg() -> Arg {
         .required(true)
         .value_parser(arg_into_component)
         .allow_negative_numbers(true)
+        .allow_hyphen_values(true)
         //.value_parser(value_parser!(Component))
         .help("Path component")
 }
 
+fn compound_arg() -> Arg {
+    Arg::new("compound")
+        .long("compound")
+        .action(ArgAction::SetTrue)
+        .help("Treat known multi-part extensions like tar.gz as one extension")
+}
+
 fn paths_arg() -> Arg {
     Arg::new("path")
-        .required(true)
         .action(ArgAction::Append)
-        .help("Path strings")
+        .help("Path strings, or lines of stdin if none are given")
         //.value_parser(value_parser!(TypedPathBuf))
         .value_parser(ValueParser::os_string())
 }
@@ -108,7 +186,18 @@ pub fn get_command() -> Command {
     Command::new("get")
         .about("Read a path component [default]")
         .arg_required_else_help(true)
-        .args([normalize_arg(), component_arg(), paths_arg()])
+        .args([
+            normalize_arg(),
+            parse_as_unix_arg(),
+            parse_as_win_arg(),
+            parse_as_uri_arg(),
+            parse_as_git_arg(),
+            null_arg(),
+            output_arg(),
+            compound_arg(),
+            component_arg(),
+            paths_arg(),
+        ])
         .after_help(components_help_section())
 }
 
@@ -117,7 +206,7 @@ pub fn has_command() -> Command {
         .about("Check if a path component exists")
         .arg_required_else_help(true)
         .args(true_false_args())
-        .args([component_arg(), paths_arg()])
+        .args([compound_arg(), component_arg(), paths_arg()])
         .after_help(components_help_section())
 }
 
@@ -125,7 +214,7 @@ fn remove_command() -> Command {
     Command::new("delete")
         .about("Remove a path component")
         .arg_required_else_help(true)
-        .args([component_arg(), paths_arg()])
+        .args([compound_arg(), component_arg(), paths_arg()])
         .after_help(components_help_section())
 }
 
@@ -137,7 +226,7 @@ fn replace_command() -> Command {
         .args([Arg::new("str")
             .required(true)
             .value_parser(ValueParser::os_string())])
-        .args([component_arg(), paths_arg()])
+        .args([compound_arg(), component_arg(), paths_arg()])
         .after_help(components_help_section())
 }
 
@@ -149,7 +238,7 @@ fn set_command() -> Command {
         .args([Arg::new("str")
             .required(true)
             .value_parser(ValueParser::os_string())])
-        .args([component_arg(), paths_arg()])
+        .args([compound_arg(), component_arg(), paths_arg()])
         .after_help(components_help_section())
 }
 
@@ -176,14 +265,30 @@ fn is_command() -> Command {
         .about("Ask questions about a file path")
         .arg_required_else_help(true)
         .args(true_false_args())
-        .args([question_arg(), paths_arg()])
+        .args([
+            question_arg(),
+            paths_arg(),
+            target_arg().help("Platform to check paths against, otherwise the type of each path"),
+        ])
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .help("Print the rules each invalid path breaks"),
+        )
         .after_help(questions_help_section())
 }
 
+fn target_arg() -> Arg {
+    Arg::new("target")
+        .long("target")
+        .value_name("PLATFORM")
+        .value_parser(value_parser!(Platform))
+}
+
 fn normalize_command() -> Command {
     Command::new("normalize")
         .about("Normalize a file path")
-        .arg_required_else_help(true)
         .arg(paths_arg())
 }
 
@@ -196,21 +301,204 @@ fn path_type_arg() -> Arg {
 
 fn convert_command() -> Command {
     Command::new("convert")
-        .about("Convert between unix and windows paths")
+        .about("Convert between unix, windows, and mounted windows paths")
         .arg_required_else_help(true)
-        .args([path_type_arg(), paths_arg()])
+        .args([
+            path_type_arg(),
+            paths_arg(),
+            Arg::new("from")
+                .long("from")
+                .value_name("TYPE")
+                .value_parser(value_parser!(PathKind))
+                .help("Type the paths are, to map wsl, msys or cygwin mounts back to disks"),
+        ])
+        .after_help(
+            "Windows disks convert to the mounts wsl, msys and cygwin use, like\n\
+            C:\\Users becoming /mnt/c/Users, /c/Users or /cygdrive/c/Users.\n\n\
+            Git remotes convert between the scp, ssh and https forms, like\n\
+            git@github.com:owner/repo.git becoming https://github.com/owner/repo.",
+        )
 }
 
 fn info_command() -> Command {
     Command::new("info")
         .about("Print information about paths")
-        .arg_required_else_help(true)
         .args([paths_arg()])
 }
 
 fn depth_command() -> Command {
     Command::new("depth")
         .about("Number of components before the last component.")
-        .arg_required_else_help(true)
         .args([paths_arg()])
 }
+
+fn format_command() -> Command {
+    Command::new("format")
+        .about("Build new paths from a template of components")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("template")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Template with components in braces, like {parent}/{stem}.{ext}"),
+            paths_arg(),
+        ])
+        .after_help(template_help_section())
+}
+
+fn template_help_section() -> &'static str {
+    "\x1B[4;1mTemplates:\x1B[0m\n\
+    \x20 \x1B[1m{ext}\x1B[0m        Value of the component, or nothing if missing\n\
+    \x20 \x1B[1m{ext|png}\x1B[0m    Value of the component, or png if missing\n\
+    \x20 \x1B[1m{{\x1B[0m, \x1B[1m}}\x1B[0m       Literal braces\n\
+    \n\
+    Any component can be used, including nth components like {0} or {-1}.\n"
+}
+
+fn join_command() -> Command {
+    Command::new("join")
+        .about("Join paths onto a base path")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("base")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Path to join onto"),
+            paths_arg(),
+            Arg::new("each")
+                .long("each")
+                .action(ArgAction::SetTrue)
+                .help("Join the base onto each path separately"),
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Fail on absolute paths instead of replacing the base with them"),
+        ])
+        .after_help(
+            "An absolute path replaces everything joined before it, unless --strict is given.\n\
+            With --normalize, the joined path is normalized rather than each path.",
+        )
+}
+
+fn relative_command() -> Command {
+    Command::new("relative")
+        .about("Path that leads from a base directory to each path")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("from")
+                .long("from")
+                .value_name("BASE")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Directory the relative paths start from"),
+            paths_arg(),
+        ])
+}
+
+fn common_command() -> Command {
+    Command::new("common")
+        .about("Deepest path that contains all paths")
+        .arg(paths_arg())
+        .after_help(
+            "Paths are compared component by component, without telling files from\n\
+            directories, so a single path or paths that are all the same are their\n\
+            own common path.",
+        )
+}
+
+fn pass_arg() -> Arg {
+    Arg::new("pass")
+        .long("pass")
+        .action(ArgAction::SetTrue)
+        .help("Print paths that aren't under the root unchanged instead of failing")
+}
+
+fn strip_prefix_command() -> Command {
+    Command::new("strip-prefix")
+        .about("Remove a leading path from each path")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("prefix")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Path to remove from the start"),
+            paths_arg(),
+            pass_arg(),
+        ])
+}
+
+fn rebase_command() -> Command {
+    Command::new("rebase")
+        .about("Move paths from under one root to another")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("old")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Root the paths are under"),
+            Arg::new("new")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("Root to move the paths under"),
+            paths_arg(),
+            pass_arg(),
+        ])
+        .after_help(
+            "When the new root is a different type of path, the rest of each path\n\
+            is converted like the convert command does.",
+        )
+}
+
+fn sanitize_command() -> Command {
+    Command::new("sanitize")
+        .about("Make a path component safe to use as a file name")
+        .args([
+            Arg::new("component")
+                .short('c')
+                .long("component")
+                .value_name("COMPONENT")
+                .default_value("name")
+                .value_parser(arg_into_component)
+                .allow_hyphen_values(true)
+                .help("Component to sanitize"),
+            target_arg()
+                .default_value("portable")
+                .help("Platform the name must be valid on"),
+            Arg::new("replacement")
+                .short('r')
+                .long("replacement")
+                .value_name("STRING")
+                .default_value("_")
+                .value_parser(ValueParser::os_string())
+                .help("Put in place of forbidden characters, or nothing to remove them"),
+            Arg::new("max-length")
+                .long("max-length")
+                .value_name("BYTES")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Cut names down to this length, otherwise the platform's limit"),
+            paths_arg(),
+        ])
+        .after_help(
+            "Reserved names like CON get the replacement added, as CON_, and the\n\
+            extension is kept when a name is cut down.  Use the component .. to\n\
+            sanitize the whole string as a single name.",
+        )
+}
+
+fn resolve_command() -> Command {
+    Command::new("resolve")
+        .about("Resolve URI references against a base URI")
+        .arg_required_else_help(true)
+        .args([
+            Arg::new("base")
+                .required(true)
+                .value_parser(ValueParser::os_string())
+                .help("URI the references are relative to"),
+            paths_arg(),
+        ])
+        .after_help(
+            "References are resolved as RFC 3986 describes, like ../d?y against\n\
+            http://a/b/c becoming http://a/d?y.  With --normalize, the resolved\n\
+            URI is normalized.",
+        )
+}
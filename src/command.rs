@@ -1,7 +1,11 @@
-use clap::builder::PossibleValue;
-use clap::ValueEnum;
+#[cfg(feature = "cli")]
+use clap::{builder::PossibleValue, ValueEnum};
 use typed_path::PathType;
 
+use crate::git::RemoteForm;
+#[cfg(feature = "cli")]
+use crate::valid::Platform;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     Get,
@@ -14,6 +18,14 @@ pub enum Command {
     Convert,
     Info,
     Depth,
+    Format,
+    Join,
+    Relative,
+    Common,
+    StripPrefix,
+    Rebase,
+    Sanitize,
+    Resolve,
 }
 
 impl TryFrom<&str> for Command {
@@ -32,6 +44,14 @@ impl TryFrom<&str> for Command {
             "convert" => Convert,
             "info" => Info,
             "depth" => Depth,
+            "format" => Format,
+            "join" => Join,
+            "relative" => Relative,
+            "common" => Common,
+            "strip-prefix" => StripPrefix,
+            "rebase" => Rebase,
+            "sanitize" => Sanitize,
+            "resolve" => Resolve,
             _ => Err(())?,
         };
         Ok(command)
@@ -39,6 +59,7 @@ impl TryFrom<&str> for Command {
 }
 
 // is this necessary?
+#[cfg(feature = "cli")]
 impl ValueEnum for Command {
     fn value_variants<'a>() -> &'a [Self] {
         &[
@@ -50,8 +71,16 @@ impl ValueEnum for Command {
             Command::Is,
             Command::Normalize,
             Command::Convert,
-            //Command::Info,
+            Command::Info,
             Command::Depth,
+            Command::Format,
+            Command::Join,
+            Command::Relative,
+            Command::Common,
+            Command::StripPrefix,
+            Command::Rebase,
+            Command::Sanitize,
+            Command::Resolve,
         ]
     }
 
@@ -68,6 +97,14 @@ impl ValueEnum for Command {
             Convert => "convert",
             Info => "info",
             Depth => "depth",
+            Format => "format",
+            Join => "join",
+            Relative => "relative",
+            Common => "common",
+            StripPrefix => "strip-prefix",
+            Rebase => "rebase",
+            Sanitize => "sanitize",
+            Resolve => "resolve",
         };
         Some(PossibleValue::new(s))
     }
@@ -80,8 +117,23 @@ pub enum Question {
     Unix,
     Windows,
     Normalized,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Valid,
+}
+
+impl Question {
+    /// Whether the question compares each path against another path
+    pub fn has_reference(self) -> bool {
+        matches!(
+            self,
+            Question::StartsWith | Question::EndsWith | Question::Contains
+        )
+    }
 }
 
+#[cfg(feature = "cli")]
 impl ValueEnum for Question {
     fn value_variants<'a>() -> &'a [Self] {
         &[
@@ -90,6 +142,10 @@ impl ValueEnum for Question {
             Question::Unix,
             Question::Windows,
             Question::Normalized,
+            Question::StartsWith,
+            Question::EndsWith,
+            Question::Contains,
+            Question::Valid,
         ]
     }
 
@@ -101,6 +157,10 @@ impl ValueEnum for Question {
             Unix => "unix",
             Windows => "windows",
             Normalized => "normalized",
+            StartsWith => "starts-with",
+            EndsWith => "ends-with",
+            Contains => "contains",
+            Valid => "valid",
         };
         Some(PossibleValue::new(s))
     }
@@ -110,26 +170,129 @@ impl ValueEnum for Question {
 pub enum PathKind {
     Unix,
     Windows,
+    /// Windows disks mounted like `/mnt/c` by wsl
+    Wsl,
+    /// Windows disks mounted like `/c` by msys and git bash
+    Msys,
+    /// Windows disks mounted like `/cygdrive/c` by cygwin
+    Cygwin,
+    /// `file:` URI of an absolute path
+    Uri,
+    /// Git remotes like `git@host:owner/repo.git`
+    Scp,
+    /// Git remotes like `ssh://git@host/owner/repo.git`
+    Ssh,
+    /// Git remotes like `https://host/owner/repo`
+    Https,
 }
 
-impl Into<PathType> for PathKind {
-    fn into(self) -> PathType {
+impl PathKind {
+    /// Form of git remote the kind is, if it is one
+    pub fn remote_form(self) -> Option<RemoteForm> {
         match self {
-            Self::Unix => PathType::Unix,
-            Self::Windows => PathType::Windows,
+            PathKind::Scp => Some(RemoteForm::Scp),
+            PathKind::Ssh => Some(RemoteForm::Ssh),
+            PathKind::Https => Some(RemoteForm::Https),
+            _ => None,
+        }
+    }
+}
+
+impl From<PathKind> for PathType {
+    fn from(kind: PathKind) -> Self {
+        match kind {
+            PathKind::Windows => PathType::Windows,
+            PathKind::Unix
+            | PathKind::Wsl
+            | PathKind::Msys
+            | PathKind::Cygwin
+            | PathKind::Uri
+            | PathKind::Scp
+            | PathKind::Ssh
+            | PathKind::Https => PathType::Unix,
         }
     }
 }
 
+#[cfg(feature = "cli")]
 impl ValueEnum for PathKind {
     fn value_variants<'a>() -> &'a [Self] {
-        &[PathKind::Unix, PathKind::Windows]
+        &[
+            PathKind::Unix,
+            PathKind::Windows,
+            PathKind::Wsl,
+            PathKind::Msys,
+            PathKind::Cygwin,
+            PathKind::Uri,
+            PathKind::Scp,
+            PathKind::Ssh,
+            PathKind::Https,
+        ]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
         let s = match self {
             Self::Unix => "unix",
             Self::Windows => "win",
+            Self::Wsl => "wsl",
+            Self::Msys => "msys",
+            Self::Cygwin => "cygwin",
+            Self::Uri => "uri",
+            Self::Scp => "scp",
+            Self::Ssh => "ssh",
+            Self::Https => "https",
+        };
+        Some(PossibleValue::new(s))
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ValueEnum for Platform {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Platform::Unix,
+            Platform::Windows,
+            Platform::MacOs,
+            Platform::Portable,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let s = match self {
+            Self::Unix => "unix",
+            Self::Windows => "windows",
+            Self::MacOs => "macos",
+            Self::Portable => "portable",
+        };
+        Some(PossibleValue::new(s))
+    }
+}
+
+/// How the app prints results, only part of the library with the `cli`
+/// feature
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    JsonLines,
+}
+
+#[cfg(feature = "cli")]
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            OutputFormat::Text,
+            OutputFormat::Json,
+            OutputFormat::JsonLines,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let s = match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::JsonLines => "jsonl",
         };
         Some(PossibleValue::new(s))
     }
@@ -1,4 +1,6 @@
+use crate::error::PathmutError;
 use crate::Action;
+use std::fmt;
 use typed_path::{
     PathType, TypedPath, TypedPathBuf, WindowsComponent, WindowsEncoding, WindowsPath,
     WindowsPrefix,
@@ -6,7 +8,7 @@ use typed_path::{
 
 // use clap::{builder::PossibleValue, ValueEnum};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Component {
     Extension,
     Stem,
@@ -15,14 +17,36 @@ pub enum Component {
     Parent,
     Disk,
     Nth(isize),
+    /// Components from the start up to but not including the end, where
+    /// missing bounds extend to either end of the path
+    Range(Option<isize>, Option<isize>),
+    /// Every extension after the prefix, like `tar.gz`
+    Extensions,
+    /// The nth extension after the prefix
+    NthExtension(isize),
+    /// Extension including known compound suffixes like `tar.gz`,
+    /// otherwise the last extension
+    CompoundExtension,
     // more windows prefixes exist
     // https://docs.rs/typed-path/0.10.0/typed_path/enum.WindowsPrefix.html#variant.Disk
-    // URIs later
-    // scheme, route, anchor, query params, domain, server
-    // Example lib: https://docs.rs/http/latest/http/uri/struct.Uri.html
-    // authority doesn't segment between user:pass
-    // Also url crate: https://docs.rs/url/latest/url/
-    // this crate has all the set methods I'd want
+    /// Parts of a URI, which paths don't have
+    Scheme,
+    User,
+    Password,
+    Host,
+    Port,
+    /// Path of a URI, which the path components act on
+    Route,
+    Query,
+    /// Value of a query parameter, or of its nth repeat
+    QueryParam(Vec<u8>, Option<isize>),
+    /// Every distinct key in the query
+    QueryKeys,
+    Fragment,
+    /// Parts of a git remote, the directories before the repository and
+    /// the repository name without `.git`
+    Owner,
+    Repo,
 }
 
 // may not need this because of ValueEnum
@@ -30,17 +54,7 @@ impl TryFrom<&str> for Component {
     type Error = ();
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        use Component::*;
-        let comp = match s {
-            "ext" => Extension,
-            "stem" => Stem,
-            "prefix" => Prefix,
-            "name" => Name,
-            "parent" => Parent,
-            "disk" => Disk,
-            _ => Nth(s.parse::<isize>().map_err(|_| ())?),
-        };
-        Ok(comp)
+        arg_into_component(s).map_err(|_| ())
     }
 }
 
@@ -56,12 +70,82 @@ pub fn arg_into_component(s: &str) -> Result<Component, String> {
             "name" => Name,
             "parent" => Parent,
             "disk" => Disk,
-            _ => Err("invalid component")?,
+            "exts" => Extensions,
+            "scheme" => Scheme,
+            "user" => User,
+            "password" => Password,
+            "host" => Host,
+            "port" => Port,
+            "route" => Route,
+            "query" => Query,
+            "fragment" => Fragment,
+            "keys" => QueryKeys,
+            "owner" => Owner,
+            "repo" => Repo,
+            _ => {
+                if let Some(Ok(n)) = s.strip_prefix("ext:").map(str::parse::<isize>) {
+                    NthExtension(n)
+                } else if let Some(param) = s.strip_prefix("query:") {
+                    match param.rsplit_once(':').map(|(k, n)| (k, n.parse::<isize>())) {
+                        Some((key, Ok(n))) => QueryParam(key.into(), Some(n)),
+                        _ => QueryParam(param.into(), None),
+                    }
+                } else if let Some((start, end)) = s.split_once("..") {
+                    let bound = |b: &str| match b {
+                        "" => Ok(None),
+                        b => b.parse::<isize>().map(Some).map_err(|_| "invalid range"),
+                    };
+                    Range(bound(start)?, bound(end)?)
+                } else {
+                    Err("invalid component")?
+                }
+            }
         };
         Ok(component)
     }
 }
 
+/// Name of the component as it is given on the command line
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Component::*;
+        let name = match self {
+            Extension | CompoundExtension => "ext",
+            Stem => "stem",
+            Prefix => "prefix",
+            Name => "name",
+            Parent => "parent",
+            Disk => "disk",
+            Extensions => "exts",
+            Scheme => "scheme",
+            User => "user",
+            Password => "password",
+            Host => "host",
+            Port => "port",
+            Route => "route",
+            Query => "query",
+            Fragment => "fragment",
+            QueryKeys => "keys",
+            Owner => "owner",
+            Repo => "repo",
+            Nth(n) => return write!(f, "{n}"),
+            NthExtension(n) => return write!(f, "ext:{n}"),
+            Range(start, end) => {
+                let bound = |b: &Option<isize>| b.map(|b| b.to_string()).unwrap_or_default();
+                return write!(f, "{}..{}", bound(start), bound(end));
+            }
+            QueryParam(key, n) => {
+                write!(f, "query:{}", String::from_utf8_lossy(key))?;
+                return match n {
+                    Some(n) => write!(f, ":{n}"),
+                    None => Ok(()),
+                };
+            }
+        };
+        f.write_str(name)
+    }
+}
+
 // todo: make my own typed value parser
 //struct ComponentParser;
 //impl clap::builder::TypedValueParser for ComponentParser {
@@ -97,7 +181,8 @@ fn split_file_at_dot(file: &[u8]) -> (&[u8], Option<&[u8]>) {
     // Referencing std::path::split_file_at_dot
     // https://doc.rust-lang.org/stable/src/std/path.rs.html#340
     let slice = file;
-    if slice == b".." {
+    // an empty name, like the missing name of `/`, has no dot to split at
+    if slice.is_empty() || slice == b".." {
         return (file, None);
     }
 
@@ -110,8 +195,95 @@ fn split_file_at_dot(file: &[u8]) -> (&[u8], Option<&[u8]>) {
     (before, Some(after))
 }
 
+/// Multi-part extensions that are treated as one by `CompoundExtension`
+pub const COMPOUND_EXTENSIONS: &[&str] = &[
+    "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz", "tar.lz4", "tar.lzma", "tar.br", "tar.Z",
+    "d.ts", "d.mts", "d.cts", "min.js", "min.css",
+];
+
+/// Extensions after the file prefix, in order
+fn extensions(file: &[u8]) -> Vec<&[u8]> {
+    match split_file_at_dot(file) {
+        (_, Some(after)) => after.split(|b| *b == b'.').collect(),
+        (_, None) => Vec::new(),
+    }
+}
+
+/// Split the file at a known compound extension, or otherwise at its last dot
+fn split_file_at_compound(file: &[u8]) -> (&[u8], Option<&[u8]>) {
+    for compound in COMPOUND_EXTENSIONS {
+        let compound = compound.as_bytes();
+        if file.len() > compound.len() + 1 {
+            let (before, after) = file.split_at(file.len() - compound.len());
+            if before.ends_with(b".") && after.eq_ignore_ascii_case(compound) {
+                return (&before[..before.len() - 1], Some(after));
+            }
+        }
+    }
+
+    match extensions(file).last() {
+        Some(ext) => (&file[..file.len() - ext.len() - 1], Some(ext)),
+        None => (file, None),
+    }
+}
+
+/// File name with the extension appended, or without one if it is empty
+fn join_extension(before: &[u8], ext: &[u8]) -> Vec<u8> {
+    if ext.is_empty() {
+        before.to_vec()
+    } else {
+        [before, b".", ext].concat()
+    }
+}
+
+/// Index into a sequence of length `len`, where negative indices count
+/// backwards from the end.  Returns `None` when before the first element.
+pub(crate) fn nth_index(n: isize, len: usize) -> Option<usize> {
+    if n >= 0 {
+        Some(n.unsigned_abs())
+    } else {
+        len.checked_sub(n.unsigned_abs())
+    }
+}
+
+/// Bounds of a range within a sequence of length `len`, where negative
+/// bounds count backwards from the end and bounds outside are clamped
+fn range_bounds(start: Option<isize>, end: Option<isize>, len: usize) -> (usize, usize) {
+    let clamp = |n: isize| nth_index(n, len).unwrap_or(0).min(len);
+    let start = start.map(clamp).unwrap_or(0);
+    let end = end.map(clamp).unwrap_or(len);
+    (start, end.max(start))
+}
+
+pub(crate) fn path_type(path: &TypedPath) -> PathType {
+    match path {
+        TypedPath::Unix(_) => PathType::Unix,
+        TypedPath::Windows(_) => PathType::Windows,
+    }
+}
+
+/// Join path components back into a path of the given type
+pub(crate) fn join_components<'a>(
+    components: impl IntoIterator<Item = &'a [u8]>,
+    path_type: PathType,
+) -> TypedPathBuf {
+    let mut path = TypedPath::new(b"", path_type).to_path_buf();
+    for component in components {
+        path.push(component);
+    }
+    path
+}
+
 impl Component {
-    pub fn action(self, action: &Action, path: &TypedPath) -> Vec<u8> {
+    /// Treat the extension as possibly compound, like `tar.gz`
+    pub fn compound(self) -> Self {
+        match self {
+            Component::Extension => Component::CompoundExtension,
+            other => other,
+        }
+    }
+
+    pub fn action(&self, action: &Action, path: &TypedPath) -> Vec<u8> {
         match action {
             Action::Get => self.get(path),
             Action::Set(s) => self.set(path, s),
@@ -120,7 +292,107 @@ impl Component {
         }
     }
 
-    pub fn get(self, path: &TypedPath) -> Vec<u8> {
+    /// Whether paths of this type can have the component at all
+    pub fn applies_to(&self, path: &TypedPath) -> bool {
+        match self {
+            Component::Disk => path.is_windows(),
+            Component::Owner | Component::Repo => false,
+            component => !component.is_uri_part(),
+        }
+    }
+
+    /// Fail when the component doesn't apply to the path, or when it is
+    /// the nth component or extension past the end, allowing `extra`
+    /// indices past it
+    fn check_range(&self, path: &TypedPath, extra: usize) -> Result<(), PathmutError> {
+        if !self.applies_to(path) {
+            return Err(PathmutError::NotApplicable);
+        }
+        let (n, len) = match self {
+            Component::Nth(n) => (*n, path.components().count()),
+            Component::NthExtension(n) => {
+                (*n, extensions(path.file_name().unwrap_or_default()).len())
+            }
+            _ => return Ok(()),
+        };
+        match nth_index(n, len) {
+            Some(index) if index < len + extra => Ok(()),
+            _ => Err(PathmutError::OutOfRange(self.clone())),
+        }
+    }
+
+    pub fn try_action(&self, action: &Action, path: &TypedPath) -> Result<Vec<u8>, PathmutError> {
+        match action {
+            Action::Get => self.try_get(path),
+            Action::Set(s) => self.try_set(path, s),
+            Action::Replace(s) => self.try_replace(path, s),
+            Action::Delete => self.try_delete(path),
+        }
+    }
+
+    /// Like [`Component::get`], but fails rather than being empty when the
+    /// component can't be in the path
+    pub fn try_get(&self, path: &TypedPath) -> Result<Vec<u8>, PathmutError> {
+        self.check_range(path, 0)?;
+        Ok(self.get(path))
+    }
+
+    /// Like [`Component::set`], but fails rather than leaving the path as
+    /// it is.  The nth component or extension can be one past the end,
+    /// which appends it, but extensions need a file name to be added to.
+    pub fn try_set(&self, path: &TypedPath, value: &[u8]) -> Result<Vec<u8>, PathmutError> {
+        use Component::*;
+        let extension = matches!(
+            self,
+            Extension | Extensions | NthExtension(_) | CompoundExtension
+        );
+        if extension && path.file_name().is_none() {
+            return Err(PathmutError::NotApplicable);
+        }
+        self.check_range(path, 1)?;
+        let invalid = match self {
+            Component::Disk => !matches!(value, [] | [b'a'..=b'z' | b'A'..=b'Z']),
+            Component::NthExtension(_) => value.contains(&b'.'),
+            _ => false,
+        };
+        if invalid {
+            return Err(PathmutError::InvalidValue(self.clone(), value.to_vec()));
+        }
+        Ok(self.set(path, value))
+    }
+
+    /// Like [`Component::replace`], which leaves paths without the
+    /// component alone, even when paths of their type never have it.  Only
+    /// fails on a value the component can't be set to.
+    pub fn try_replace(&self, path: &TypedPath, value: &[u8]) -> Result<Vec<u8>, PathmutError> {
+        if self.has(path) {
+            self.try_set(path, value)
+        } else {
+            Ok(path.as_bytes().to_vec())
+        }
+    }
+
+    /// Like [`Component::delete`], which leaves paths without the
+    /// component alone, so it never fails
+    pub fn try_delete(&self, path: &TypedPath) -> Result<Vec<u8>, PathmutError> {
+        Ok(self.delete(path))
+    }
+
+    /// Fail when getting or setting the component on a route, the path of
+    /// a URI or git remote, would fail.  Replacing and deleting never do.
+    pub(crate) fn check_route(
+        &self,
+        action: &Action,
+        route: &TypedPath,
+    ) -> Result<(), PathmutError> {
+        match action {
+            Action::Get => self.try_get(route).map(drop),
+            Action::Set(value) => self.try_set(route, value).map(drop),
+            Action::Replace(_) | Action::Delete => Ok(()),
+        }
+    }
+
+    pub fn get(&self, path: &TypedPath) -> Vec<u8> {
         use Component::*;
         match self {
             Extension => path.extension().unwrap_or_default().into(),
@@ -131,11 +403,15 @@ impl Component {
                 .parent()
                 .map(|p| p.as_bytes().to_vec())
                 .unwrap_or_default(),
+            Scheme | User | Password | Host | Port | Route | Query | QueryParam(..) | QueryKeys
+            | Fragment | Owner | Repo => Vec::new(),
             Disk => match path {
                 TypedPath::Unix(_) => "".into(),
                 TypedPath::Windows(w) => match w.components().next() {
                     Some(WindowsComponent::Prefix(prefix)) => match prefix.kind() {
-                        WindowsPrefix::Disk(disk) => [disk].into(),
+                        WindowsPrefix::Disk(disk) | WindowsPrefix::VerbatimDisk(disk) => {
+                            [disk].into()
+                        }
                         _ => "".into(),
                     },
                     _ => "".into(),
@@ -143,30 +419,47 @@ impl Component {
             },
             Nth(n) => {
                 let num_components: usize = path.components().count();
-                let index: usize = if n >= 0 {
-                    let positive: usize = n.try_into().unwrap();
-                    positive
-                } else {
-                    let positive: usize = (-n).try_into().unwrap();
-                    if positive > num_components {
-                        // index is behind first component
-                        return Vec::new();
-                    }
-                    num_components - positive
+                let Some(index) = nth_index(*n, num_components) else {
+                    // index is behind first component
+                    return Vec::new();
                 };
                 path.components()
                     .nth(index)
                     .map(|c| c.as_bytes().to_vec())
                     .unwrap_or_default()
             }
+            Range(start, end) => {
+                let components: Vec<&[u8]> = path.components().map(|c| c.as_bytes()).collect();
+                let (start, end) = range_bounds(*start, *end, components.len());
+                join_components(components[start..end].iter().copied(), path_type(path)).into_vec()
+            }
+            Extensions => path
+                .file_name()
+                .map(split_file_at_dot)
+                .and_then(|(_, after)| after)
+                .unwrap_or_default()
+                .into(),
+            NthExtension(n) => {
+                let exts = extensions(path.file_name().unwrap_or_default());
+                nth_index(*n, exts.len())
+                    .and_then(|i| exts.get(i))
+                    .map(|ext| ext.to_vec())
+                    .unwrap_or_default()
+            }
+            CompoundExtension => path
+                .file_name()
+                .map(split_file_at_compound)
+                .and_then(|(_, after)| after)
+                .unwrap_or_default()
+                .into(),
         }
     }
 
-    pub fn has(self, path: &TypedPath) -> bool {
+    pub fn has(&self, path: &TypedPath) -> bool {
         !self.get(path).is_empty()
     }
 
-    pub fn set(self, path: &TypedPath, value: &[u8]) -> Vec<u8> {
+    pub fn set(&self, path: &TypedPath, value: &[u8]) -> Vec<u8> {
         use Component::*;
         match self {
             Extension => path.with_extension(value).into_vec(),
@@ -211,17 +504,21 @@ impl Component {
                     .join(path.file_name().unwrap_or_default())
                     .into_vec()
             }
+            Scheme | User | Password | Host | Port | Route | Query | QueryParam(..) | QueryKeys
+            | Fragment | Owner | Repo => path.to_path_buf().into_vec(),
             Disk => match path {
                 TypedPath::Unix(_) => path.to_path_buf().into_vec(),
                 TypedPath::Windows(w) => {
                     let mut original = w.components();
                     let mut new = original.clone();
-                    let has_prefix = match new.next() {
+                    // a verbatim disk like \\?\C: stays verbatim
+                    let (has_prefix, verbatim) = match new.next() {
                         Some(WindowsComponent::Prefix(prefix)) => match prefix.kind() {
-                            WindowsPrefix::Disk(_) => true,
-                            _ => false,
+                            WindowsPrefix::Disk(_) => (true, false),
+                            WindowsPrefix::VerbatimDisk(_) => (true, true),
+                            _ => (false, false),
                         },
-                        _ => false,
+                        _ => (false, false),
                     };
 
                     let no_disk: &typed_path::Path<WindowsEncoding> = if has_prefix {
@@ -231,19 +528,19 @@ impl Component {
                         original.as_path()
                     };
 
-                    if value.len() == 0 {
+                    if value.is_empty() {
                         return original
                             .as_path::<WindowsEncoding>()
                             .to_path_buf()
                             .into_vec();
                     }
 
-                    // TEST: what happens if disk is more one char?
-                    // what if 0 chars
-
-                    // this is so garbage
-                    let disk_str = format!(r"{}:", String::from_utf8(vec![value[0]]).unwrap());
-                    let disk_path = WindowsPath::new(&disk_str);
+                    // only the first byte is used, try_set rejects the rest
+                    let disk = match verbatim {
+                        true => [br"\\?\".as_slice(), &[value[0], b':']].concat(),
+                        false => vec![value[0], b':'],
+                    };
+                    let disk_path = WindowsPath::new(&disk);
                     let mut new_path = disk_path.to_path_buf();
                     new_path.push(no_disk);
 
@@ -255,16 +552,9 @@ impl Component {
                 // todo
 
                 let num_components: usize = path.components().count();
-                let index: usize = if n >= 0 {
-                    let positive: usize = n.try_into().unwrap();
-                    positive
-                } else {
-                    let positive: usize = (-n).try_into().unwrap();
-                    if positive > num_components {
-                        // index is behind first component
-                        return Vec::new();
-                    }
-                    num_components - positive
+                let Some(index) = nth_index(*n, num_components) else {
+                    // index is behind first component
+                    return Vec::new();
                 };
 
                 // what if n == number of components?
@@ -289,10 +579,54 @@ impl Component {
                     .map(|p| p.into_vec())
                     .unwrap_or_default()
             }
+            Range(start, end) => {
+                let components: Vec<&[u8]> = path.components().map(|c| c.as_bytes()).collect();
+                let (start, end) = range_bounds(*start, *end, components.len());
+                let value = Some(value).filter(|v| !v.is_empty());
+                let kept = components[..start]
+                    .iter()
+                    .copied()
+                    .chain(value)
+                    .chain(components[end..].iter().copied());
+                join_components(kept, path_type(path)).into_vec()
+            }
+            Extensions => {
+                let Some(file) = path.file_name() else {
+                    return path.to_path_buf().into_vec();
+                };
+                let (before, _) = split_file_at_dot(file);
+                path.with_file_name(join_extension(before, value))
+                    .into_vec()
+            }
+            NthExtension(n) => {
+                let Some(file) = path.file_name() else {
+                    return path.to_path_buf().into_vec();
+                };
+                let (before, _) = split_file_at_dot(file);
+                let mut exts = extensions(file);
+                match nth_index(*n, exts.len()) {
+                    Some(i) if i < exts.len() && value.is_empty() => {
+                        exts.remove(i);
+                    }
+                    Some(i) if i < exts.len() => exts[i] = value,
+                    Some(i) if i == exts.len() && !value.is_empty() => exts.push(value),
+                    _ => return path.to_path_buf().into_vec(),
+                }
+                let name = [before].into_iter().chain(exts).collect::<Vec<_>>();
+                path.with_file_name(name.join(&b'.')).into_vec()
+            }
+            CompoundExtension => {
+                let Some(file) = path.file_name() else {
+                    return path.to_path_buf().into_vec();
+                };
+                let (before, _) = split_file_at_compound(file);
+                path.with_file_name(join_extension(before, value))
+                    .into_vec()
+            }
         }
     }
 
-    pub fn replace(self, path: &TypedPath, value: &[u8]) -> Vec<u8> {
+    pub fn replace(&self, path: &TypedPath, value: &[u8]) -> Vec<u8> {
         //println!("{:?} {:?}", path, value);
         if self.has(path) {
             self.set(path, value)
@@ -339,3 +673,36 @@ impl Component {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nth_extension_without_name() {
+        for path in ["/", "", "..", "C:"] {
+            let path = TypedPath::derive(path);
+            assert_eq!(
+                Component::NthExtension(0).try_get(&path),
+                Err(PathmutError::OutOfRange(Component::NthExtension(0)))
+            );
+            assert_eq!(
+                Component::NthExtension(0).try_delete(&path),
+                Ok(path.as_bytes().to_vec())
+            );
+
+            assert_eq!(
+                Component::NthExtension(-1).try_get(&path),
+                Err(PathmutError::OutOfRange(Component::NthExtension(-1)))
+            );
+            assert_eq!(
+                Component::NthExtension(0).try_set(&path, b"md"),
+                Err(PathmutError::NotApplicable)
+            );
+            assert_eq!(
+                Component::Extension.try_set(&path, b"md"),
+                Err(PathmutError::NotApplicable)
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::command::PathKind;
+use crate::component::Component;
+use crate::uri::to_file_uri;
+use typed_path::{TypedPath, TypedPathBuf};
+
+/// Where a unix environment on windows mounts the windows disks, for
+/// kinds of path that are one
+fn mount_root(kind: PathKind) -> Option<&'static [u8]> {
+    match kind {
+        PathKind::Wsl => Some(b"/mnt/"),
+        PathKind::Msys => Some(b"/"),
+        PathKind::Cygwin => Some(b"/cygdrive/"),
+        PathKind::Unix
+        | PathKind::Windows
+        | PathKind::Uri
+        | PathKind::Scp
+        | PathKind::Ssh
+        | PathKind::Https => None,
+    }
+}
+
+/// Windows path of a path under the mount root, like `C:\Users` for
+/// `/mnt/c/Users`
+fn from_mount(path: &TypedPath, root: &[u8]) -> Option<TypedPathBuf> {
+    if !path.is_unix() {
+        return None;
+    }
+    let rest = path.as_bytes().strip_prefix(root)?;
+    let (disk, rest) = match rest.iter().position(|b| *b == b'/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, &b"/"[..]),
+    };
+    let [disk] = disk else {
+        return None;
+    };
+    if !disk.is_ascii_alphabetic() {
+        return None;
+    }
+
+    let rooted = TypedPath::unix(rest).with_windows_encoding();
+    let windows = Component::Disk.set(&rooted.to_path(), &[disk.to_ascii_uppercase()]);
+    Some(TypedPathBuf::from_windows(windows))
+}
+
+/// Path under the mount root of a windows path with a disk, like
+/// `/mnt/c/Users` for `C:\Users`.  Other paths only change separators.
+/// Returns `None` for a path relative to the current directory of a disk,
+/// like `C:foo`, which has no place under the mount.
+fn to_mount(path: &TypedPath, root: &[u8]) -> Option<TypedPathBuf> {
+    let disk = Component::Disk.get(path);
+    let [disk] = disk[..] else {
+        return Some(path.with_unix_encoding());
+    };
+    let rest = TypedPathBuf::from_windows(Component::Disk.set(path, b"")).with_unix_encoding();
+    if !rest.as_bytes().is_empty() && !rest.as_bytes().starts_with(b"/") {
+        return None;
+    }
+
+    let mut mounted = root.to_vec();
+    mounted.push(disk.to_ascii_lowercase());
+    mounted.extend_from_slice(rest.as_bytes());
+    Some(TypedPathBuf::from_unix(mounted))
+}
+
+/// Convert the path to another kind of path.  When the path is of a kind
+/// that mounts windows disks, `from` maps it back to the windows disk
+/// first, so `/mnt/c/Users` from wsl converts to `C:\Users`.  Returns
+/// `None` when converting a relative path to a URI, a drive-relative path
+/// like `C:foo` to a mount, or a path to a git remote, which only
+/// [`GitRemote::convert`] does.
+///
+/// [`GitRemote::convert`]: crate::GitRemote::convert
+pub fn convert(path: &TypedPath, to: PathKind, from: Option<PathKind>) -> Option<TypedPathBuf> {
+    let windows = from
+        .and_then(mount_root)
+        .and_then(|root| from_mount(path, root));
+    let path = match &windows {
+        Some(windows) => windows.to_path(),
+        None => *path,
+    };
+
+    let converted = match mount_root(to) {
+        Some(root) => to_mount(&path, root)?,
+        None => match to {
+            PathKind::Uri => TypedPathBuf::from_unix(to_file_uri(&path)?),
+            PathKind::Windows => path.with_windows_encoding(),
+            PathKind::Scp | PathKind::Ssh | PathKind::Https => return None,
+            _ => path.with_unix_encoding(),
+        },
+    };
+    Some(converted)
+}
@@ -0,0 +1,32 @@
+use std::fmt;
+
+use crate::component::Component;
+
+/// Reason a change can't be made to a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathmutError {
+    /// Index past either end of the components or extensions
+    OutOfRange(Component),
+    /// The component or change doesn't fit the path, like the disk of a
+    /// unix path or a prefix the path doesn't start with
+    NotApplicable,
+    /// Value the component can't be set to, like a disk that isn't a letter
+    InvalidValue(Component, Vec<u8>),
+}
+
+impl fmt::Display for PathmutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathmutError::OutOfRange(component) => {
+                write!(f, "component {component} is out of range")
+            }
+            PathmutError::NotApplicable => write!(f, "doesn't apply to the path"),
+            PathmutError::InvalidValue(component, value) => {
+                let value = String::from_utf8_lossy(value);
+                write!(f, "'{value}' isn't a valid {component}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathmutError {}
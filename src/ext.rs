@@ -0,0 +1,220 @@
+use crate::command::Question;
+use crate::component::Component;
+use crate::error::PathmutError;
+use crate::ops::depth;
+use crate::valid::Platform;
+use crate::Action;
+use typed_path::{TypedPath, TypedPathBuf, UnixPath, WindowsPath};
+
+/// Components and questions called on the paths of `typed-path`, like
+/// `path.get(Component::Stem)`
+pub trait PathMutExt {
+    fn to_typed_path(&self) -> TypedPath<'_>;
+
+    /// The component, or nothing when the path doesn't have it
+    fn get(&self, component: Component) -> Vec<u8> {
+        component.get(&self.to_typed_path())
+    }
+
+    fn has(&self, component: Component) -> bool {
+        component.has(&self.to_typed_path())
+    }
+
+    /// Path with the component set to the value
+    fn with_component(&self, component: Component, value: &[u8]) -> TypedPathBuf {
+        let path = self.to_typed_path();
+        same_type(&path, component.set(&path, value))
+    }
+
+    /// Path with the component set to the value, only if it has it
+    fn replace_component(&self, component: Component, value: &[u8]) -> TypedPathBuf {
+        let path = self.to_typed_path();
+        same_type(&path, component.replace(&path, value))
+    }
+
+    fn without_component(&self, component: Component) -> TypedPathBuf {
+        let path = self.to_typed_path();
+        same_type(&path, component.delete(&path))
+    }
+
+    /// Like [`PathMutExt::get`], but fails when the component doesn't
+    /// apply to the path or is out of range
+    fn try_get(&self, component: Component) -> Result<Vec<u8>, PathmutError> {
+        component.try_get(&self.to_typed_path())
+    }
+
+    /// Like [`PathMutExt::with_component`], but fails rather than leaving
+    /// the path alone, like for the disk of a unix path
+    fn try_with_component(
+        &self,
+        component: Component,
+        value: &[u8],
+    ) -> Result<TypedPathBuf, PathmutError> {
+        let path = self.to_typed_path();
+        Ok(same_type(&path, component.try_set(&path, value)?))
+    }
+
+    fn try_replace_component(
+        &self,
+        component: Component,
+        value: &[u8],
+    ) -> Result<TypedPathBuf, PathmutError> {
+        let path = self.to_typed_path();
+        Ok(same_type(&path, component.try_replace(&path, value)?))
+    }
+
+    fn try_without_component(&self, component: Component) -> Result<TypedPathBuf, PathmutError> {
+        let path = self.to_typed_path();
+        Ok(same_type(&path, component.try_delete(&path)?))
+    }
+
+    /// The component for [`Action::Get`], otherwise the changed path
+    fn action(&self, component: Component, action: &Action) -> Vec<u8> {
+        component.action(action, &self.to_typed_path())
+    }
+
+    /// Like [`PathMutExt::action`], but fails rather than hiding why the
+    /// component can't be used
+    fn try_action(&self, component: Component, action: &Action) -> Result<Vec<u8>, PathmutError> {
+        component.try_action(action, &self.to_typed_path())
+    }
+
+    /// Number of components before the last
+    fn depth(&self) -> usize {
+        depth(&self.to_typed_path())
+    }
+
+    /// Answer the question about the path.  Questions that compare paths
+    /// need the reference and fail without it, and `valid` checks the rules
+    /// of the platform, or of the path's own type without one.
+    fn is(
+        &self,
+        question: Question,
+        reference: Option<&TypedPath>,
+        platform: Option<Platform>,
+    ) -> Result<bool, PathmutError> {
+        question.ask(&self.to_typed_path(), reference, platform)
+    }
+}
+
+/// Path of the same type as another
+fn same_type(path: &TypedPath, bytes: Vec<u8>) -> TypedPathBuf {
+    match path {
+        TypedPath::Unix(_) => TypedPathBuf::from_unix(bytes),
+        TypedPath::Windows(_) => TypedPathBuf::from_windows(bytes),
+    }
+}
+
+impl PathMutExt for TypedPath<'_> {
+    fn to_typed_path(&self) -> TypedPath<'_> {
+        *self
+    }
+}
+
+impl PathMutExt for TypedPathBuf {
+    fn to_typed_path(&self) -> TypedPath<'_> {
+        self.to_path()
+    }
+}
+
+impl PathMutExt for UnixPath {
+    fn to_typed_path(&self) -> TypedPath<'_> {
+        TypedPath::Unix(self)
+    }
+}
+
+impl PathMutExt for WindowsPath {
+    fn to_typed_path(&self) -> TypedPath<'_> {
+        TypedPath::Windows(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_path(path: &(impl PathMutExt + ?Sized)) {
+        assert_eq!(path.get(Component::Stem), b"cat");
+        assert_eq!(path.try_get(Component::Nth(-2)), Ok(b"2024".to_vec()));
+        assert_eq!(path.depth(), 2);
+        assert_eq!(
+            path.try_with_component(Component::Nth(-2), b"2025")
+                .map(TypedPathBuf::into_vec),
+            Ok(b"photos/2025/cat.jpg".to_vec())
+        );
+        assert_eq!(
+            path.without_component(Component::Extension).into_vec(),
+            b"photos/2024/cat"
+        );
+    }
+
+    #[test]
+    fn every_path_type() {
+        check_path(&TypedPath::derive("photos/2024/cat.jpg"));
+        check_path(&TypedPathBuf::from("photos/2024/cat.jpg"));
+        check_path(UnixPath::new("photos/2024/cat.jpg"));
+        check_path(WindowsPath::new("photos/2024/cat.jpg"));
+    }
+
+    #[test]
+    fn keeps_path_type() {
+        let path = WindowsPath::new(r"C:\photos\cat.jpg");
+        let renamed = path.with_component(Component::Stem, b"dog");
+        assert_eq!(renamed, TypedPathBuf::from_windows(r"C:\photos\dog.jpg"));
+        assert!(UnixPath::new("a")
+            .with_component(Component::Stem, b"b")
+            .is_unix());
+    }
+
+    #[test]
+    fn is() {
+        let path = UnixPath::new("/photos/cat.jpg");
+        let photos = TypedPath::derive("/photos");
+        assert_eq!(path.is(Question::Absolute, None, None), Ok(true));
+        assert_eq!(path.is(Question::StartsWith, Some(&photos), None), Ok(true));
+        assert_eq!(path.is(Question::Contains, Some(&photos), None), Ok(true));
+        assert_eq!(
+            path.is(Question::StartsWith, None, None),
+            Err(PathmutError::NotApplicable)
+        );
+        assert_eq!(
+            path.is(Question::EndsWith, None, None),
+            Err(PathmutError::NotApplicable)
+        );
+
+        let path = UnixPath::new("cat:1.jpg");
+        assert_eq!(path.is(Question::Valid, None, None), Ok(true));
+        assert_eq!(
+            path.is(Question::Valid, None, Some(Platform::Windows)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn try_methods() {
+        let path = UnixPath::new("/photos/cat.jpg");
+        assert_eq!(
+            path.try_with_component(Component::Disk, b"C"),
+            Err(PathmutError::NotApplicable)
+        );
+        assert_eq!(
+            path.try_get(Component::Nth(5)),
+            Err(PathmutError::OutOfRange(Component::Nth(5)))
+        );
+        assert_eq!(
+            path.try_replace_component(Component::Disk, b"C"),
+            Ok(TypedPathBuf::from_unix("/photos/cat.jpg"))
+        );
+        assert_eq!(
+            path.try_without_component(Component::Disk),
+            Ok(TypedPathBuf::from_unix("/photos/cat.jpg"))
+        );
+        assert_eq!(
+            path.try_with_component(Component::NthExtension(0), b"a.b"),
+            Err(PathmutError::InvalidValue(
+                Component::NthExtension(0),
+                b"a.b".to_vec()
+            ))
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crate::component::Component;
+use crate::error::PathmutError;
+use crate::uri::route_path;
+use crate::Action;
+use typed_path::TypedPath;
+
+/// Way a git remote is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteForm {
+    /// `user@host:owner/repo.git`
+    Scp,
+    /// `ssh://user@host:port/owner/repo.git`
+    Ssh,
+    /// `https://host/owner/repo`
+    Https,
+}
+
+/// Location of a git repository on a server, like
+/// `git@github.com:owner/repo.git`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRemote {
+    /// Missing for scp-like remotes
+    pub scheme: Option<Vec<u8>>,
+    pub user: Option<Vec<u8>>,
+    pub host: Vec<u8>,
+    pub port: Option<Vec<u8>>,
+    /// Path of the repository on the host, without a leading `/` after
+    /// the host of a URL
+    pub route: Vec<u8>,
+}
+
+impl GitRemote {
+    /// Read a URL like `https://host/owner/repo` or an scp-like remote
+    /// like `git@host:owner/repo.git`.  Returns `None` for anything else,
+    /// including windows paths like `C:/repo`.
+    pub fn parse(remote: &[u8]) -> Option<Self> {
+        let scheme_end = remote.windows(3).position(|w| w == b"://");
+        let (scheme, authority, route) = match scheme_end {
+            Some(i) => {
+                let rest = &remote[i + 3..];
+                let slash = rest.iter().position(|b| *b == b'/').unwrap_or(rest.len());
+                let route = rest[slash..].strip_prefix(b"/").unwrap_or_default();
+                (Some(&remote[..i]), &rest[..slash], route)
+            }
+            None => {
+                let colon = remote.iter().position(|b| *b == b':')?;
+                let authority = &remote[..colon];
+                if authority.contains(&b'/')
+                    || matches!(authority, [letter] if letter.is_ascii_alphabetic())
+                {
+                    return None;
+                }
+                (None, authority, &remote[colon + 1..])
+            }
+        };
+
+        let (user, hostport) = match authority.iter().rposition(|b| *b == b'@') {
+            Some(i) => (Some(&authority[..i]), &authority[i + 1..]),
+            None => (None, authority),
+        };
+        let (host, port) = match (scheme, hostport.iter().rposition(|b| *b == b':')) {
+            (Some(_), Some(i)) => (&hostport[..i], Some(&hostport[i + 1..])),
+            _ => (hostport, None),
+        };
+        if host.is_empty() {
+            return None;
+        }
+
+        Some(GitRemote {
+            scheme: scheme.map(<[u8]>::to_vec),
+            user: user.map(<[u8]>::to_vec),
+            host: host.to_vec(),
+            port: port.map(<[u8]>::to_vec),
+            route: route.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if let Some(scheme) = &self.scheme {
+            bytes.extend_from_slice(scheme);
+            bytes.extend_from_slice(b"://");
+        }
+        if let Some(user) = &self.user {
+            bytes.extend_from_slice(user);
+            bytes.push(b'@');
+        }
+        bytes.extend_from_slice(&self.host);
+        match (&self.scheme, &self.port) {
+            (Some(_), Some(port)) => {
+                bytes.push(b':');
+                bytes.extend_from_slice(port);
+                bytes.push(b'/');
+            }
+            (Some(_), None) => bytes.push(b'/'),
+            (None, _) => bytes.push(b':'),
+        }
+        bytes.extend_from_slice(&self.route);
+        bytes
+    }
+
+    /// The same repository written in another form.  Https remotes drop the
+    /// user, port and `.git` suffix, and the others get `git` as the user
+    /// and the suffix when they are missing.
+    pub fn convert(&self, form: RemoteForm) -> Self {
+        let mut remote = self.clone();
+        let git_suffix = remote.route.ends_with(b".git");
+        match form {
+            RemoteForm::Https => {
+                remote.scheme = Some(b"https".to_vec());
+                remote.user = None;
+                remote.port = None;
+                if git_suffix {
+                    remote.route.truncate(remote.route.len() - 4);
+                }
+                return remote;
+            }
+            RemoteForm::Ssh => remote.scheme = Some(b"ssh".to_vec()),
+            RemoteForm::Scp => {
+                remote.scheme = None;
+                remote.port = None;
+            }
+        }
+        if self.scheme.as_deref() != Some(b"ssh") && self.scheme.is_some() {
+            remote.port = None;
+        }
+        remote.user.get_or_insert_with(|| b"git".to_vec());
+        if !git_suffix && !remote.route.is_empty() {
+            remote.route.extend_from_slice(b".git");
+        }
+        remote
+    }
+
+    pub fn route_path(&self) -> TypedPath {
+        route_path(&self.route)
+    }
+
+    /// Position the repository name starts at in the route
+    fn repo_start(&self) -> usize {
+        let route = self.route.strip_suffix(b"/").unwrap_or(&self.route);
+        route.iter().rposition(|b| *b == b'/').map_or(0, |i| i + 1)
+    }
+
+    /// Repository name without the `.git` suffix
+    pub fn repo(&self) -> &[u8] {
+        let repo = &self.route[self.repo_start()..];
+        let repo = repo.strip_suffix(b"/").unwrap_or(repo);
+        repo.strip_suffix(b".git").unwrap_or(repo)
+    }
+
+    /// Every directory before the repository, like a user or a group
+    pub fn owner(&self) -> &[u8] {
+        let owner = &self.route[..self.repo_start()];
+        owner.strip_suffix(b"/").unwrap_or(owner)
+    }
+
+    fn set_repo(&mut self, repo: &[u8]) {
+        let start = self.repo_start();
+        let old = self.repo().len();
+        self.route.splice(start..start + old, repo.iter().copied());
+    }
+
+    fn set_owner(&mut self, owner: &[u8]) {
+        let mut route = owner.to_vec();
+        if !route.is_empty() && !route.ends_with(b"/") {
+            route.push(b'/');
+        }
+        route.extend_from_slice(&self.route[self.repo_start()..]);
+        self.route = route;
+    }
+}
+
+impl Component {
+    pub fn action_git(&self, action: &Action, remote: &GitRemote) -> Vec<u8> {
+        match action {
+            Action::Get => self.get_git(remote),
+            Action::Set(s) => self.set_git(remote, s),
+            Action::Replace(s) => self.replace_git(remote, s),
+            Action::Delete => self.delete_git(remote),
+        }
+    }
+
+    /// Like [`Component::action_git`], but fails when getting or setting a
+    /// component remotes don't have, or the nth component past the end of
+    /// the route
+    pub fn try_action_git(
+        &self,
+        action: &Action,
+        remote: &GitRemote,
+    ) -> Result<Vec<u8>, PathmutError> {
+        use Component::*;
+        if !matches!(self, Scheme | User | Host | Port | Route | Owner | Repo) {
+            self.check_route(action, &remote.route_path())?;
+        }
+        Ok(self.action_git(action, remote))
+    }
+
+    /// Part of the remote, where path components act on the route
+    pub fn get_git(&self, remote: &GitRemote) -> Vec<u8> {
+        use Component::*;
+        match self {
+            Scheme => remote.scheme.clone().unwrap_or_default(),
+            User => remote.user.clone().unwrap_or_default(),
+            Host => remote.host.clone(),
+            Port => remote.port.clone().unwrap_or_default(),
+            Route => remote.route.clone(),
+            Owner => remote.owner().to_vec(),
+            Repo => remote.repo().to_vec(),
+            Password | Query | QueryParam(..) | QueryKeys | Fragment | Disk => Vec::new(),
+            path_component => path_component.get(&remote.route_path()),
+        }
+    }
+
+    pub fn has_git(&self, remote: &GitRemote) -> bool {
+        !self.get_git(remote).is_empty()
+    }
+
+    /// Change part of the remote.  Setting a scheme or port on an scp-like
+    /// remote turns it into a URL.
+    pub fn set_git(&self, remote: &GitRemote, value: &[u8]) -> Vec<u8> {
+        use Component::*;
+        let mut remote = remote.clone();
+        let value = value.to_vec();
+        match self {
+            Scheme => remote.scheme = Some(value),
+            User => remote.user = Some(value),
+            Host => remote.host = value,
+            Port => {
+                remote.scheme.get_or_insert_with(|| b"ssh".to_vec());
+                remote.port = Some(value);
+            }
+            Route => remote.route = value,
+            Owner => remote.set_owner(&value),
+            Repo => remote.set_repo(&value),
+            Password | Query | QueryParam(..) | QueryKeys | Fragment | Disk => {}
+            path_component => remote.route = path_component.set(&remote.route_path(), &value),
+        }
+        remote.to_bytes()
+    }
+
+    pub fn replace_git(&self, remote: &GitRemote, value: &[u8]) -> Vec<u8> {
+        if self.has_git(remote) {
+            self.set_git(remote, value)
+        } else {
+            remote.to_bytes()
+        }
+    }
+
+    /// Remove part of the remote, where the scheme, host and repository
+    /// can't be removed
+    pub fn delete_git(&self, remote: &GitRemote) -> Vec<u8> {
+        use Component::*;
+        let mut remote = remote.clone();
+        match self {
+            User => remote.user = None,
+            Port => remote.port = None,
+            Route => remote.route.clear(),
+            Owner => remote.set_owner(b""),
+            Scheme | Host | Repo | Password | Query | QueryParam(..) | QueryKeys | Fragment
+            | Disk => {}
+            path_component => remote.route = path_component.delete(&remote.route_path()),
+        }
+        remote.to_bytes()
+    }
+}
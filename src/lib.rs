@@ -1,15 +1,36 @@
 #![feature(path_file_prefix)]
 #![feature(iter_intersperse)]
 
+#[cfg(feature = "cli")]
 mod app;
 mod command;
 mod component;
+mod convert;
+mod error;
+mod ext;
+mod git;
+mod ops;
+mod relation;
+mod template;
+mod uri;
+mod valid;
 
+#[cfg(feature = "cli")]
 pub use app::build as build_app;
+#[cfg(feature = "cli")]
 pub use app::get_command;
 
 pub use command::*;
 pub use component::*;
+pub use convert::*;
+pub use error::*;
+pub use ext::*;
+pub use git::*;
+pub use ops::*;
+pub use relation::*;
+pub use template::*;
+pub use uri::*;
+pub use valid::*;
 
 #[derive(Debug, Clone)]
 pub enum Action<'a> {
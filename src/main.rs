@@ -1,287 +1,646 @@
-use clap::ArgMatches;
-use std::env;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, ValueEnum};
 use std::ffi::OsString;
-use std::io::{self, IsTerminal, Read};
+use std::io::{self, BufRead, IsTerminal};
 use std::process::ExitCode;
-use typed_path::{
-    PathType, TypedComponent, TypedPath, TypedPathBuf, UnixComponent, WindowsComponent,
-};
+use typed_path::{TypedPath, TypedPathBuf};
 
 use pathmut::*;
 
-#[derive(Debug)]
+mod output;
+use output::{Json, Output};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParseAs {
     Derive,
     Windows,
     Unix,
+    Uri,
+    Git,
+}
+
+/// Path strings given as arguments after the first `skip` of them, or
+/// otherwise each record of stdin read lazily as it arrives
+fn path_strings(
+    args: &ArgMatches,
+    skip: usize,
+    separator: u8,
+) -> Box<dyn Iterator<Item = Vec<u8>> + '_> {
+    if let Some(paths) = args.get_many::<OsString>("path").filter(|p| p.len() > skip) {
+        return Box::new(
+            paths
+                .skip(skip)
+                .map(|path| path.as_encoded_bytes().to_vec()),
+        );
+    }
+
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        missing_paths();
+    }
+
+    Box::new(stdin.lock().split(separator).map_while(Result::ok))
+}
+
+fn missing_paths() -> ! {
+    clap::Error::raw(
+        ErrorKind::MissingRequiredArgument,
+        "The following required arguments were not provided:\n  <path>...\n",
+    )
+    .exit()
+}
+
+fn parse_path(path: &[u8], parse_as: ParseAs) -> TypedPathBuf {
+    // -u and -w take the string as it is, even when it looks like a URI
+    if parse_as == ParseAs::Derive && is_file_uri(path) {
+        return from_file_uri(path, None).expect("file uri");
+    }
+    match parse_as {
+        ParseAs::Derive => TypedPath::derive(path).to_path_buf(),
+        ParseAs::Windows => TypedPath::windows(path).to_path_buf(),
+        ParseAs::Unix => TypedPath::unix(path).to_path_buf(),
+        // only commands that handle URIs and remotes themselves accept them
+        ParseAs::Uri | ParseAs::Git => unreachable!("URIs and remotes aren't parsed as paths"),
+    }
 }
 
 fn parse_paths(
     args: &ArgMatches,
     normalize: bool,
     parse_as: ParseAs,
+    separator: u8,
 ) -> impl Iterator<Item = TypedPathBuf> + '_ {
-    args.get_many::<OsString>("path")
-        .expect("required")
-        .map(|path| path.as_encoded_bytes())
-        .map(move |path| match parse_as {
-            ParseAs::Derive => TypedPath::derive(path),
-            ParseAs::Windows => TypedPath::windows(path),
-            ParseAs::Unix => TypedPath::unix(path),
-        })
-        .map(move |path| {
-            if normalize {
-                path.normalize()
-            } else {
-                path.to_path_buf()
+    path_strings(args, 0, separator)
+        .map(move |path| parse_path(&path, parse_as))
+        .map(move |path| if normalize { path.normalize() } else { path })
+}
+
+fn parse_uri(uri: &[u8], normalize: bool) -> Uri {
+    let uri = Uri::parse(uri);
+    if normalize {
+        uri.normalize()
+    } else {
+        uri
+    }
+}
+
+/// Report a problem with a single path without stopping the others
+fn path_error(output: &mut Output, message: &str, path: &[u8]) {
+    let message = format!("{message} '{}'", String::from_utf8_lossy(path));
+    eprintln!("error: {message}");
+    output.error(Json::Bytes(path), &message);
+}
+
+/// Exit code of the first path that couldn't be changed
+#[derive(Default)]
+struct Failed(Option<ExitCode>);
+
+impl Failed {
+    fn fail(&mut self, code: ExitCode) {
+        self.0.get_or_insert(code);
+    }
+
+    /// Write the changed path, or report why it couldn't be changed
+    fn path(
+        &mut self,
+        output: &mut Output,
+        input: &[u8],
+        changed: Result<Vec<u8>, PathmutError>,
+        message: impl FnOnce(&PathmutError) -> String,
+    ) {
+        match changed {
+            Ok(changed) => output.path(input, &changed),
+            Err(error) => {
+                path_error(output, &message(&error), input);
+                self.fail(exit_code(&error));
             }
-        })
+        }
+    }
+
+    fn code(self) -> ExitCode {
+        self.0.unwrap_or(ExitCode::SUCCESS)
+    }
+}
+
+fn info_text(path: &TypedPathBuf, info: &Info) -> Vec<u8> {
+    let path_type = match (info.unix, info.windows_prefix) {
+        (true, _) => "unix".to_string(),
+        (false, None) => "windows".to_string(),
+        (false, Some(kind)) => format!("windows, {kind} prefix"),
+    };
+
+    let mut lines: Vec<Vec<u8>> = vec![
+        path.as_bytes().to_vec(),
+        format!("{:>10}: {path_type}", "type").into(),
+        format!("{:>10}: {}", "absolute", info.absolute).into(),
+        format!("{:>10}: {}", "normalized", info.normalized).into(),
+    ];
+
+    for (name, value) in &info.values {
+        lines.push([format!("{name:>10}: ").as_bytes(), value].concat());
+    }
+
+    lines.push(format!("{:>10}:", "components").into());
+    let count = info.components.len() as isize;
+    for (i, component) in info.components.iter().enumerate() {
+        let i = i as isize;
+        let ordinals = format!("{i:>7} {:>3}  ", i - count);
+        lines.push([ordinals.as_bytes(), component].concat());
+    }
+
+    lines.join(&b'\n')
+}
+
+fn info_json<'a>(path: &'a TypedPathBuf, info: &'a Info) -> Json<'a> {
+    let path_type = if info.unix { "unix" } else { "windows" };
+    let mut fields = vec![
+        ("input", Json::Bytes(path.as_bytes())),
+        ("type", Json::Str(path_type)),
+        (
+            "windows_prefix",
+            info.windows_prefix.map(Json::Str).unwrap_or(Json::Null),
+        ),
+        ("absolute", Json::Bool(info.absolute)),
+        ("normalized", Json::Bool(info.normalized)),
+    ];
+    fields.extend(
+        info.values
+            .iter()
+            .map(|(name, value)| (*name, Json::Bytes(value))),
+    );
+    let components = info.components.iter().map(|c| Json::Bytes(c));
+    fields.push(("components", Json::List(components.collect())));
+    Json::Object(fields)
+}
+
+/// Message for a path a change can't be made to, where `path` is the path
+/// or route it was applied to
+fn mut_error(path_mut: &PathMut, error: &PathmutError, path: &TypedPath) -> String {
+    if *error != PathmutError::NotApplicable {
+        return format!("{error} for");
+    }
+    match path_mut {
+        PathMut::Get(component)
+        | PathMut::Set(component, _)
+        | PathMut::Replace(component, _)
+        | PathMut::Delete(component) => format!("{component} doesn't apply to"),
+        PathMut::Join { .. } => "cannot join absolute path".to_string(),
+        PathMut::Convert { to, .. } if *to != PathKind::Uri => {
+            "no mount path for drive-relative path".to_string()
+        }
+        PathMut::Convert { .. } => match windows_prefix(path) {
+            Some(kind @ ("verbatim" | "device")) => format!("no file URI for {kind} path"),
+            _ if path.is_relative() => "no file URI for relative path".to_string(),
+            _ => error.to_string(),
+        },
+        PathMut::Relative(base) => format!("no relative path from '{}' to", base.to_string_lossy()),
+        PathMut::StripPrefix(old) | PathMut::Rebase { old, .. } => {
+            format!("'{}' doesn't contain", old.to_string_lossy())
+        }
+        _ => error.to_string(),
+    }
+}
+
+/// Exit code for each kind of error, after the 1 of a failed path and the
+/// 2 of wrong arguments
+fn exit_code(error: &PathmutError) -> ExitCode {
+    match error {
+        PathmutError::OutOfRange(_) => ExitCode::from(3),
+        PathmutError::NotApplicable => ExitCode::from(4),
+        PathmutError::InvalidValue(..) => ExitCode::from(5),
+    }
 }
 
-/// Equality between TypedPathBuf that don't use .components(), which
-/// does not yield . and ..
-fn not_normal_path_eq(left: &TypedPathBuf, right: &TypedPathBuf) -> bool {
-    use TypedPathBuf::*;
-    match (left, right) {
-        (Unix(left), Unix(right)) => left.as_bytes() == right.as_bytes(),
-        (Windows(left), Windows(right)) => left.as_bytes() == right.as_bytes(),
-        _ => false,
+/// Reasons a path answers false
+type Explain<'a, P> = &'a dyn Fn(&P) -> Vec<String>;
+
+/// Answer a question for each path, combining the answers with either
+/// any or all.  Text output stops as soon as the answer is known, unless
+/// each path is explained by the reasons it fails.
+fn answer_paths<P: AsRef<[u8]>>(
+    paths: impl Iterator<Item = P>,
+    test: impl Fn(&P) -> bool,
+    explain: Option<Explain<P>>,
+    cmd_args: &ArgMatches,
+    output: &mut Output,
+) -> ExitCode {
+    let all = cmd_args.get_flag("all");
+    let print = cmd_args.get_flag("print");
+
+    let mut answer = all;
+    for path in paths {
+        let path_answer = test(&path);
+        match explain {
+            Some(explain) => output.explained(path.as_ref(), path_answer, &explain(&path)),
+            None => output.answer(path.as_ref(), path_answer),
+        }
+        answer = if all {
+            answer && path_answer
+        } else {
+            answer || path_answer
+        };
+        if answer != all && output.is_text() && explain.is_none() {
+            break;
+        }
+    }
+
+    output.aggregate(if all { "all" } else { "any" }, answer, print);
+    if answer || print {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
 
 fn main() -> ExitCode {
-    let app = build_app();
-    let stdin = io::stdin();
+    let matches = build_app().get_matches();
 
-    // manually fetch args, so it can be overwritten by piped input
-    let mut args: Vec<String> = env::args_os().map(|x| x.into_string().unwrap()).collect();
+    let Some((cmd, cmd_args)) = matches.subcommand() else {
+        return ExitCode::SUCCESS;
+    };
 
-    if !stdin.is_terminal() {
-        // user is piping
-        let mut v = Vec::new();
-        let mut handle = stdin.lock(); // sometimes freezes
-        handle.read_to_end(&mut v).unwrap();
-        let s = String::from_utf8(v).unwrap();
-        if !s.is_empty() {
-            args.push(s);
+    // check if cmd is a command or component
+    let default_matches;
+    let (cmd, cmd_args) = match Command::try_from(cmd) {
+        Ok(cmd) => (cmd, cmd_args),
+        Err(()) => {
+            // assume subcommand is get, and reparse with cmd as the component
+            let trailing = cmd_args.get_many::<OsString>("").into_iter().flatten();
+            let args = ["get", cmd].map(OsString::from).into_iter();
+            default_matches = get_command().get_matches_from(args.chain(trailing.cloned()));
+            (Command::Get, &default_matches)
         }
-    }
+    };
 
-    let matches = app.get_matches_from(args.clone());
+    // global flags may appear before or after the command
+    let flag = |name| matches.get_flag(name) || cmd_args.get_flag(name);
 
-    let normalized_first = *matches.get_one::<bool>("normalize").unwrap();
-    let parse_as = if *matches.get_one::<bool>("as-windows").unwrap() {
+    let normalized_first = flag("normalize");
+    let parse_as = if flag("as-windows") {
         ParseAs::Windows
-    } else if *matches.get_one::<bool>("as-unix").unwrap() {
+    } else if flag("as-unix") {
         ParseAs::Unix
+    } else if flag("as-uri") {
+        ParseAs::Uri
+    } else if flag("as-git") {
+        ParseAs::Git
     } else {
         ParseAs::Derive
     };
+    let uri_command = matches!(
+        cmd,
+        Command::Get
+            | Command::Set
+            | Command::Delete
+            | Command::Replace
+            | Command::Has
+            | Command::Normalize
+            | Command::Resolve
+    );
+    let git_command = matches!(
+        cmd,
+        Command::Get
+            | Command::Set
+            | Command::Delete
+            | Command::Replace
+            | Command::Has
+            | Command::Convert
+    );
+    let conflict = match parse_as {
+        ParseAs::Uri if !uri_command => Some("--as-uri"),
+        ParseAs::Git if !git_command => Some("--as-git"),
+        _ => None,
+    };
+    if let Some(flag) = conflict {
+        let name = cmd.to_possible_value().expect("command name");
+        let message = format!("'{}' doesn't work with {flag}\n", name.get_name());
+        clap::Error::raw(ErrorKind::ArgumentConflict, message).exit();
+    }
+    let separator = if flag("null") { b'\0' } else { b'\n' };
+    let format = match cmd_args.value_source("output") {
+        Some(ValueSource::CommandLine) => cmd_args.get_one::<OutputFormat>("output"),
+        _ => matches.get_one::<OutputFormat>("output"),
+    };
+    let mut output = Output::new(*format.expect("default"), separator);
 
-    // can I hoist the path work up here?  Can I parse paths upfront?
-
-    //let matches = app.get_matches();
-
-    if let Some((cmd, cmd_args)) = matches.subcommand() {
-        // check if cmd is a command or component
-        if let Ok(cmd) = Command::try_from(cmd) {
-            // if command is is
-            match cmd {
-                Command::Depth => {
-                    let paths = parse_paths(cmd_args, normalized_first, parse_as);
-
-                    for path in paths {
-                        println!(
-                            "{}",
-                            path.components()
-                                .filter(|c| {
-                                    if let TypedComponent::Windows(WindowsComponent::Prefix(_)) = c
-                                    {
-                                        return false;
-                                    } else {
-                                        return true;
-                                    }
-                                })
-                                .count()
-                                - 1
-                        );
-                    }
+    let path_mut = match cmd {
+        Command::Depth => {
+            for path in parse_paths(cmd_args, normalized_first, parse_as, separator) {
+                output.number(path.as_bytes(), depth(&path.to_path()) as isize);
+            }
+            return ExitCode::SUCCESS;
+        }
+        Command::Info => {
+            for path in parse_paths(cmd_args, normalized_first, parse_as, separator) {
+                let info = Info::of(&path.to_path());
+                if output.is_text() {
+                    output.write(&info_text(&path, &info));
+                } else {
+                    output.record(info_json(&path, &info));
                 }
-                Command::Info => {
-                    let paths = parse_paths(cmd_args, normalized_first, parse_as);
-
-                    for path in paths {
-                        println!("{}", path.to_string_lossy());
-                        println!(
-                            "      type: {}",
-                            match path {
-                                TypedPathBuf::Unix(_) => "unix",
-                                TypedPathBuf::Windows(_) => "windows",
-                            }
-                        );
-                        for (component, name) in [
-                            (Component::Parent, "parent"),
-                            (Component::Name, "name"),
-                            (Component::Prefix, "prefix"),
-                            (Component::Stem, "stem"),
-                            (Component::Extension, "extension"),
-                        ] {
-                            println!(
-                                "{name:>10}: {}",
-                                String::from_utf8_lossy(&component.get(&path.to_path())),
-                            );
-                        }
-
-                        let mut offset = 0;
-                        for c in path.components() {
-                            let s = match c {
-                                TypedComponent::Unix(comp) => match comp {
-                                    UnixComponent::RootDir => "/",
-                                    UnixComponent::Normal(slice) => &String::from_utf8_lossy(slice),
-                                    _ => todo!(),
-                                },
-                                _ => todo!(),
-                            };
-
-                            let mut padding = String::new();
-                            for _ in 0..offset {
-                                padding.push_str(" ");
-                            }
-                            println!("{padding}{s}");
-                            offset += s.len();
-                            if s != "/" {
-                                offset += 1;
-                            }
-                        }
-                    }
+            }
+            return ExitCode::SUCCESS;
+        }
+        Command::Is => {
+            let question = *cmd_args.get_one::<Question>("question").expect("required");
+
+            // the first path is compared against the rest
+            let skip = question.has_reference() as usize;
+            let reference = question.has_reference().then(|| {
+                let Some(reference) = cmd_args
+                    .get_many::<OsString>("path")
+                    .and_then(|mut p| p.next())
+                else {
+                    missing_paths();
+                };
+                let reference = parse_path(reference.as_encoded_bytes(), parse_as);
+                if normalized_first {
+                    reference.normalize()
+                } else {
+                    reference
                 }
-                Command::Is => {
-                    let mut paths = parse_paths(cmd_args, normalized_first, parse_as);
-
-                    let question = cmd_args.get_one::<Question>("question").expect("required");
-                    let all = cmd_args.get_flag("all");
-                    let print = cmd_args.get_flag("print");
-
-                    let answer = match (question, all) {
-                        (Question::Absolute, true) => paths.all(|path| path.is_absolute()),
-                        (Question::Absolute, false) => paths.any(|path| path.is_absolute()),
-                        (Question::Relative, true) => paths.all(|path| path.is_relative()),
-                        (Question::Relative, false) => paths.any(|path| path.is_relative()),
-                        (Question::Unix, true) => paths.all(|path| path.is_unix()),
-                        (Question::Unix, false) => paths.any(|path| path.is_unix()),
-                        (Question::Windows, true) => paths.all(|path| path.is_windows()),
-                        (Question::Windows, false) => paths.any(|path| path.is_windows()),
-                        (Question::Normalized, true) => {
-                            paths.all(|path| not_normal_path_eq(&path, &path.normalize()))
-                        }
-                        (Question::Normalized, false) => {
-                            paths.any(|path| not_normal_path_eq(&path, &path.normalize()))
-                        }
-                    };
-                    if print {
-                        if answer {
-                            println!("true");
-                        } else {
-                            println!("false");
-                        }
-                    } else if !answer {
-                        return ExitCode::FAILURE;
+            });
+            let reference = reference.as_ref().map(TypedPathBuf::to_path);
+
+            let paths = path_strings(cmd_args, skip, separator)
+                .map(|path| parse_path(&path, parse_as))
+                .map(|path| {
+                    if normalized_first {
+                        path.normalize()
+                    } else {
+                        path
                     }
-                }
-                Command::Has => {
-                    let mut paths = parse_paths(cmd_args, normalized_first, parse_as);
+                });
+
+            let target = cmd_args.get_one::<Platform>("target").copied();
+            let test = |path: &TypedPathBuf| {
+                question
+                    .ask(&path.to_path(), reference.as_ref(), target)
+                    .expect("reference of a comparison")
+            };
+            let explain = |path: &TypedPathBuf| {
+                let platform = match (target, path.is_unix()) {
+                    (Some(target), _) => target,
+                    (None, true) => Platform::Unix,
+                    (None, false) => Platform::Windows,
+                };
+                invalid_reasons(&path.to_path(), platform)
+                    .iter()
+                    .map(|reason| reason.to_string())
+                    .collect()
+            };
+            let explain: Option<Explain<TypedPathBuf>> =
+                (question == Question::Valid && cmd_args.get_flag("explain")).then_some(&explain);
+
+            return answer_paths(paths, test, explain, cmd_args, &mut output);
+        }
+        Command::Has => {
+            let mut component = cmd_args
+                .get_one::<Component>("component")
+                .expect("required")
+                .clone();
+            if cmd_args.get_flag("compound") {
+                component = component.compound();
+            }
 
-                    let component = cmd_args
-                        .get_one::<Component>("component")
-                        .expect("required");
-                    let all = cmd_args.get_flag("all");
-                    let print = cmd_args.get_flag("print");
+            if parse_as == ParseAs::Uri {
+                let uris = path_strings(cmd_args, 0, separator);
+                let test = |uri: &Vec<u8>| component.has_uri(&parse_uri(uri, normalized_first));
+                return answer_paths(uris, test, None, cmd_args, &mut output);
+            }
+            if parse_as == ParseAs::Git {
+                let remotes = path_strings(cmd_args, 0, separator);
+                let test = |remote: &Vec<u8>| {
+                    GitRemote::parse(remote).is_some_and(|remote| component.has_git(&remote))
+                };
+                return answer_paths(remotes, test, None, cmd_args, &mut output);
+            }
 
-                    let answer = if all {
-                        paths.all(|path| component.has(&path.to_path()))
-                    } else {
-                        paths.any(|path| component.has(&path.to_path()))
-                    };
-
-                    if print {
-                        if answer {
-                            println!("true");
-                        } else {
-                            println!("false");
-                        }
-                    } else if !answer {
-                        return ExitCode::FAILURE;
-                    }
-                }
-                Command::Normalize => {
-                    parse_paths(cmd_args, normalized_first, parse_as)
-                        .map(|path| path.normalize())
-                        .for_each(|path| println!("{}", path.to_string_lossy()));
+            let paths = parse_paths(cmd_args, normalized_first, parse_as, separator);
+            let test = |path: &TypedPathBuf| component.has(&path.to_path());
+
+            return answer_paths(paths, test, None, cmd_args, &mut output);
+        }
+        Command::Normalize => PathMut::Normalize,
+        Command::Convert => {
+            let to = *cmd_args.get_one::<PathKind>("type").expect("required");
+            let from = cmd_args.get_one::<PathKind>("from").copied();
+
+            if parse_as == ParseAs::Git && to.remote_form().is_none() {
+                clap::Error::raw(
+                    ErrorKind::ArgumentConflict,
+                    "git remotes only convert to scp, ssh or https\n",
+                )
+                .exit();
+            }
+            PathMut::Convert { to, from }
+        }
+        Command::Format => {
+            let template = cmd_args.get_one::<OsString>("template").expect("required");
+            let template = Template::parse(template.as_encoded_bytes())
+                .unwrap_or_else(|e| clap::Error::raw(ErrorKind::InvalidValue, e + "\n").exit());
+            PathMut::Format(template)
+        }
+        Command::Join => {
+            let base = cmd_args.get_one::<OsString>("base").expect("required");
+            let base = parse_path(base.as_encoded_bytes(), parse_as);
+            let strict = cmd_args.get_flag("strict");
+
+            // the result is normalized, rather than each path beforehand
+            let finish = |joined: TypedPath| {
+                if normalized_first {
+                    joined.normalize().into_vec()
+                } else {
+                    joined.as_bytes().to_vec()
                 }
-                Command::Convert => {
-                    let path_type: PathType =
-                        (*cmd_args.get_one::<PathKind>("type").expect("required")).into();
-
-                    let paths = parse_paths(cmd_args, normalized_first, parse_as);
-
-                    for path in paths {
-                        let converted = match path_type {
-                            PathType::Unix => path.with_unix_encoding(),
-                            PathType::Windows => path.with_windows_encoding(),
-                        };
-                        println!("{}", converted.to_string_lossy());
+            };
+            let paths = parse_paths(cmd_args, false, parse_as, separator);
+
+            if !cmd_args.get_flag("each") {
+                let paths: Vec<TypedPathBuf> = paths.collect();
+                return match join_all(&base.to_path(), paths.iter().map(|p| p.to_path()), strict) {
+                    Ok(joined) => {
+                        let inputs = std::iter::once(&base).chain(&paths).map(|p| p.as_bytes());
+                        output.paths(inputs, &finish(joined.to_path()));
+                        ExitCode::SUCCESS
                     }
-                }
-                Command::Get | Command::Delete | Command::Replace | Command::Set => {
-                    let component = cmd_args
-                        .get_one::<Component>("component")
-                        .expect("required");
-
-                    // This requires manual labor
-
-                    let action = match cmd {
-                        Command::Get => Action::Get,
-                        Command::Delete => Action::Delete,
-                        Command::Replace => Action::Replace(
-                            cmd_args
-                                .get_one::<OsString>("str")
-                                .expect("required")
-                                .as_encoded_bytes(),
-                        ),
-                        Command::Set => Action::Set(
-                            cmd_args
-                                .get_one::<OsString>("str")
-                                .expect("required")
-                                .as_encoded_bytes(),
-                        ),
-                        _ => unreachable!(),
-                    };
-
-                    let results = parse_paths(cmd_args, normalized_first, parse_as)
-                        .map(|path| component.action(&action, &path.to_path()));
-
-                    for result in results {
-                        println!("{}", String::from_utf8_lossy(&result));
+                    Err(path) => {
+                        path_error(&mut output, "cannot join absolute path", path.as_bytes());
+                        exit_code(&PathmutError::NotApplicable)
                     }
-                }
+                };
             }
-        } else {
-            // assume subcommand is get
-            let matches = get_command().get_matches_from(args);
 
-            let action = Action::Get;
-            let component = matches.get_one::<Component>("component").expect("required");
+            let unix = base.is_unix();
+            let join = PathMut::Join { base, strict };
+            let mut failed = Failed::default();
+            for path in paths {
+                let joined = join.apply(&path.to_path()).map(|joined| match unix {
+                    true => finish(TypedPath::unix(&joined)),
+                    false => finish(TypedPath::windows(&joined)),
+                });
+                failed.path(&mut output, path.as_bytes(), joined, |error| {
+                    mut_error(&join, error, &path.to_path())
+                });
+            }
+            return failed.code();
+        }
+        Command::Relative => {
+            let base = cmd_args.get_one::<OsString>("from").expect("required");
+            PathMut::Relative(parse_path(base.as_encoded_bytes(), parse_as))
+        }
+        Command::Common => {
+            let paths: Vec<TypedPathBuf> =
+                parse_paths(cmd_args, normalized_first, parse_as, separator).collect();
+            // nothing is printed for no paths, like every other command
+            if paths.is_empty() {
+                return ExitCode::SUCCESS;
+            }
+
+            let Some(common) = common_ancestor(paths.iter().map(|path| path.to_path())) else {
+                let message = "paths have no common ancestor";
+                eprintln!("error: {message}");
+                let inputs = paths
+                    .iter()
+                    .map(|path| Json::Bytes(path.as_bytes()))
+                    .collect();
+                output.error(Json::List(inputs), message);
+                return ExitCode::FAILURE;
+            };
+            output.paths(paths.iter().map(|path| path.as_bytes()), common.as_bytes());
+            return ExitCode::SUCCESS;
+        }
+        Command::StripPrefix | Command::Rebase => {
+            let root = |id| {
+                let root = cmd_args.get_one::<OsString>(id).expect("required");
+                let root = parse_path(root.as_encoded_bytes(), parse_as);
+                if normalized_first {
+                    root.normalize()
+                } else {
+                    root
+                }
+            };
+            if cmd == Command::Rebase {
+                PathMut::Rebase {
+                    old: root("old"),
+                    new: root("new"),
+                }
+            } else {
+                PathMut::StripPrefix(root("prefix"))
+            }
+        }
+        Command::Sanitize => {
+            let component = cmd_args.get_one::<Component>("component").expect("default");
+            let mut sanitizer =
+                Sanitizer::new(*cmd_args.get_one::<Platform>("target").expect("default"));
+            sanitizer.replacement = cmd_args
+                .get_one::<OsString>("replacement")
+                .expect("default")
+                .as_encoded_bytes()
+                .to_vec();
+            if let Some(max_len) = cmd_args.get_one::<u64>("max-length") {
+                sanitizer.max_len = usize::try_from(*max_len).unwrap_or(usize::MAX);
+            }
+            if sanitizer
+                .replacement
+                .iter()
+                .any(|b| sanitizer.platform.forbids(*b))
+            {
+                clap::Error::raw(
+                    ErrorKind::InvalidValue,
+                    "replacement has characters forbidden on the target platform\n",
+                )
+                .exit();
+            }
+            PathMut::Sanitize(component.clone(), sanitizer)
+        }
+        Command::Resolve => {
+            let base = cmd_args.get_one::<OsString>("base").expect("required");
+            PathMut::Resolve(parse_uri(base.as_encoded_bytes(), normalized_first))
+        }
+        Command::Get | Command::Delete | Command::Replace | Command::Set => {
+            let mut component = cmd_args
+                .get_one::<Component>("component")
+                .expect("required")
+                .clone();
+            if cmd_args.get_flag("compound") {
+                component = component.compound();
+            }
 
-            let results = parse_paths(&matches, normalized_first, parse_as)
-                .map(|path| component.action(&action, &path.to_path()));
+            let value = || {
+                cmd_args
+                    .get_one::<OsString>("str")
+                    .expect("required")
+                    .as_encoded_bytes()
+            };
+            let action = match cmd {
+                Command::Get => Action::Get,
+                Command::Delete => Action::Delete,
+                Command::Replace => Action::Replace(value()),
+                Command::Set => Action::Set(value()),
+                _ => unreachable!(),
+            };
+
+            PathMut::from((component, action))
+        }
+    };
 
-            for result in results {
-                println!("{}", String::from_utf8_lossy(&result));
+    // references are always resolved as URIs, and remotes are converted
+    // whether or not --as-git is given
+    let parse_as = match &path_mut {
+        PathMut::Resolve(_) => ParseAs::Uri,
+        PathMut::Convert { to, .. } if to.remote_form().is_some() => ParseAs::Git,
+        _ => parse_as,
+    };
+    let mut failed = Failed::default();
+    match parse_as {
+        ParseAs::Uri => {
+            // references are resolved as given, and only the target normalized
+            let resolve = matches!(path_mut, PathMut::Resolve(_));
+            for uri in path_strings(cmd_args, 0, separator) {
+                let parsed = parse_uri(&uri, normalized_first && !resolve);
+                let mut changed = path_mut.apply_uri(&parsed);
+                if resolve && normalized_first {
+                    changed = changed.map(|target| Uri::parse(&target).normalize().to_bytes());
+                }
+                failed.path(&mut output, &uri, changed, |error| {
+                    mut_error(&path_mut, error, &parsed.route_path())
+                });
+            }
+        }
+        ParseAs::Git => {
+            for remote in path_strings(cmd_args, 0, separator) {
+                match GitRemote::parse(&remote) {
+                    Some(parsed) => {
+                        failed.path(&mut output, &remote, path_mut.apply_git(&parsed), |error| {
+                            mut_error(&path_mut, error, &parsed.route_path())
+                        })
+                    }
+                    None => {
+                        path_error(&mut output, "not a git remote", &remote);
+                        failed.fail(ExitCode::FAILURE);
+                    }
+                }
+            }
+        }
+        _ => {
+            // paths a change doesn't apply to are reported, or passed through
+            let pass =
+                matches!(cmd, Command::StripPrefix | Command::Rebase) && cmd_args.get_flag("pass");
+            for path in parse_paths(cmd_args, normalized_first, parse_as, separator) {
+                let changed = match path_mut.apply(&path.to_path()) {
+                    Err(_) if pass => Ok(path.as_bytes().to_vec()),
+                    changed => changed,
+                };
+                failed.path(&mut output, path.as_bytes(), changed, |error| {
+                    mut_error(&path_mut, error, &path.to_path())
+                });
             }
         }
     }
 
-    ExitCode::SUCCESS
+    failed.code()
 }
 
 #[cfg(test)]
@@ -310,6 +669,42 @@ mod test {
             .stdout("\\path\\file.txt\n");
     }
 
+    #[test]
+    fn null_flag() {
+        pathmut(&["-0", "get", "ext", "file.txt", "another.png"])
+            .success()
+            .stdout("txt\0png\0");
+        pathmut(&["--null", "ext", "file.txt"])
+            .success()
+            .stdout("txt\0");
+        pathmut(&["-0", "is", "-p", "absolute", "/file.txt"])
+            .success()
+            .stdout("true\0");
+        pathmut(&["-0", "depth", "/my/path"])
+            .success()
+            .stdout("2\0");
+        Command::cargo_bin("pathmut")
+            .unwrap()
+            .args(["-0", "get", "name"])
+            .write_stdin("my path/new\nline.txt\0/other.png\0")
+            .assert()
+            .success()
+            .stdout("new\nline.txt\0other.png\0");
+    }
+
+    #[test]
+    fn flags_with_default_command() {
+        pathmut(&["-u", "parent", "C:\\path\\to\\file.txt"])
+            .success()
+            .stdout("\n");
+        pathmut(&["ext", "-0", "file.txt"])
+            .success()
+            .stdout("txt\0");
+        pathmut(&["parent", "-n", "/path/to/../file.txt"])
+            .success()
+            .stdout("/path\n");
+    }
+
     #[test]
     fn parse_as_flags() {
         pathmut(&["-w", "get", "parent", "/path/to/file.txt"])
@@ -418,6 +813,56 @@ mod test {
                 .stdout("false\n");
         }
 
+        #[test]
+        fn starts_with() {
+            pathmut(&["is", "starts-with", "/foo/bar", "/foo/bar/baz"]).success();
+            pathmut(&["is", "starts-with", "/foo/bar", "/foo/bar"]).success();
+            pathmut(&["is", "starts-with", "/foo/bar", "/foo/barbaz"]).failure();
+            pathmut(&["is", "starts-with", "foo/bar", "/foo/bar/baz"]).failure();
+            pathmut(&["is", "starts-with", r"C:\foo", r"C:\foo\bar"]).success();
+            pathmut(&["is", "starts-with", r"C:\foo", r"c:\foo\bar"]).success();
+            pathmut(&["is", "starts-with", r"C:\foo", r"C:\Foo\bar"]).failure();
+            pathmut(&["is", "starts-with", "/foo", r"C:\foo\bar"]).failure();
+        }
+
+        #[test]
+        fn ends_with() {
+            pathmut(&["is", "ends-with", "bar/baz", "/foo/bar/baz"]).success();
+            pathmut(&["is", "ends-with", "ar/baz", "/foo/bar/baz"]).failure();
+            pathmut(&["is", "ends-with", "/bar/baz", "/foo/bar/baz"]).failure();
+            pathmut(&["is", "ends-with", r"d:\bar", r"D:\bar"]).success();
+        }
+
+        #[test]
+        fn contains() {
+            pathmut(&["is", "contains", "bar/baz", "/foo/bar/baz/file"]).success();
+            pathmut(&["is", "contains", "foo/baz", "/foo/bar/baz/file"]).failure();
+            pathmut(&["is", "contains", "bar", "/foo/barbaz"]).failure();
+            pathmut(&["is", "contains", r"c:\foo", r"C:\foo\bar"]).success();
+        }
+
+        #[test]
+        fn reference_any_all() {
+            pathmut(&["is", "starts-with", "--all", "/src", "/src/a", "/build/a"]).failure();
+            pathmut(&["is", "starts-with", "--any", "/src", "/src/a", "/build/a"]).success();
+            pathmut(&["is", "starts-with", "-p", "/src", "/build/a"])
+                .success()
+                .stdout("false\n");
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(["is", "starts-with", "--all", "/src"])
+                .write_stdin("/src/a\n/src/b\n")
+                .assert()
+                .success();
+        }
+
+        #[test]
+        fn reference_normalize() {
+            pathmut(&["is", "starts-with", "/a/b", "/a/c/../b/d"]).failure();
+            pathmut(&["is", "-n", "starts-with", "/a/b", "/a/c/../b/d"]).success();
+            pathmut(&["is", "-n", "starts-with", "/a/./b", "/a/b/d"]).success();
+        }
+
         #[test]
         fn any_all_conflict() {
             pathmut(&["is", "--all", "--any", "absolute", "/path/to/file.txt"]).failure();
@@ -450,6 +895,61 @@ mod test {
             pathmut(&["is", "normalized", "/my/../path"]).failure();
             pathmut(&["is", "normalized", "/my//path"]).failure();
         }
+
+        #[test]
+        fn valid() {
+            pathmut(&["is", "valid", "/my/path/file.txt"]).success();
+            pathmut(&["is", "valid", r"C:\my\file.txt"]).success();
+            pathmut(&["is", "valid", "-u", "a:b?"]).success();
+            pathmut(&["is", "valid", "-w", r"C:\my\file?.txt"]).failure();
+            pathmut(&["is", "valid", "-w", r"C:\my\nul.txt"]).failure();
+            pathmut(&["is", "valid", "-w", r"C:\my\file."]).failure();
+            pathmut(&["is", "valid", "-w", r"C:\my\file "]).failure();
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(["is", "valid"])
+                .write_stdin("a\0b\n")
+                .assert()
+                .failure();
+        }
+
+        #[test]
+        fn valid_target() {
+            pathmut(&["is", "valid", "--target", "windows", "a/b?"]).failure();
+            pathmut(&["is", "valid", "--target", "portable", "a/con"]).failure();
+            pathmut(&["is", "valid", "-u", "--target", "macos", "a:b"]).failure();
+            pathmut(&["is", "valid", "-u", "--target", "unix", "a:b"]).success();
+            pathmut(&["is", "valid", "--target", "unix", &"a/".repeat(2000)]).success();
+            pathmut(&["is", "valid", "--target", "windows", &"a/".repeat(200)]).failure();
+            pathmut(&["is", "valid", "--target", "unix", &"a".repeat(256)]).failure();
+        }
+
+        #[test]
+        fn valid_explain() {
+            pathmut(&[
+                "is",
+                "valid",
+                "--explain",
+                "--target",
+                "windows",
+                "ok",
+                "Report: <final>?.pdf",
+                "CON. ",
+            ])
+            .success()
+            .stdout(
+                "Report: <final>?.pdf: contains forbidden character ':', \
+                contains forbidden character '<', contains forbidden character '>', \
+                contains forbidden character '?'\n\
+                CON. : uses reserved name 'CON. ', has a name ending in a dot or space\n",
+            );
+            pathmut(&["--output", "jsonl", "is", "valid", "--explain", "-w", "nul"])
+                .failure()
+                .stdout(
+                    "{\"input\":\"nul\",\"output\":false,\"reasons\":[\"uses reserved name 'nul'\"]}\n\
+                    {\"any\":false}\n",
+                );
+        }
     }
 
     mod default {
@@ -515,7 +1015,7 @@ mod test {
             pathmut(&["disk", "d:\\path\\to\\file.txt"])
                 .success()
                 .stdout("D\n"); // FIXME: this performs capitalization on my behalf, which isn't what I want
-            pathmut(&["disk", "/linux/path"]).success().stdout("\n");
+            pathmut(&["disk", "/linux/path"]).code(4).stdout("");
         }
 
         #[test]
@@ -533,7 +1033,7 @@ mod test {
 
         #[test]
         fn nth_1() {
-            pathmut(&["1", "/"]).success().stdout("\n");
+            pathmut(&["1", "/"]).code(3).stdout("");
             pathmut(&["1", "/my/path/file.txt"])
                 .success()
                 .stdout("my\n");
@@ -698,8 +1198,8 @@ mod test {
                 .success()
                 .stdout("D\n"); // FIXME: this performs capitalization on my behalf, which isn't what I want
             pathmut(&["get", "disk", "/linux/path"])
-                .success()
-                .stdout("\n");
+                .code(4)
+                .stderr("error: disk doesn't apply to '/linux/path'\n");
         }
 
         #[test]
@@ -729,7 +1229,9 @@ mod test {
 
         #[test]
         fn nth_1() {
-            pathmut(&["get", "1", "/"]).success().stdout("\n");
+            pathmut(&["get", "1", "/"])
+                .code(3)
+                .stderr("error: component 1 is out of range for '/'\n");
             pathmut(&["get", "1", "/my/path/file.txt"])
                 .success()
                 .stdout("my\n");
@@ -755,28 +1257,24 @@ mod test {
         #[test]
         fn nth_outside() {
             // index == 1 more than num components
-            pathmut(&["get", "1", "/"]).success().stdout("\n");
+            pathmut(&["get", "1", "/"]).code(3).stdout("");
             pathmut(&["get", "4", "/my/path/file.txt"])
-                .success()
-                .stdout("\n");
+                .code(3)
+                .stdout("");
             pathmut(&["get", "3", "my/path/file.txt"])
-                .success()
-                .stdout("\n");
-            pathmut(&["get", "3", "my/path/file"])
-                .success()
-                .stdout("\n");
+                .code(3)
+                .stdout("");
+            pathmut(&["get", "3", "my/path/file"]).code(3).stdout("");
 
             // index == - num components
-            pathmut(&["get", "-2", "/"]).success().stdout("\n");
+            pathmut(&["get", "-2", "/"]).code(3).stdout("");
             pathmut(&["get", "-5", "/my/path/file.txt"])
-                .success()
-                .stdout("\n");
+                .code(3)
+                .stdout("");
             pathmut(&["get", "-4", "my/path/file.txt"])
-                .success()
-                .stdout("\n");
-            pathmut(&["get", "-4", "my/path/file"])
-                .success()
-                .stdout("\n");
+                .code(3)
+                .stdout("");
+            pathmut(&["get", "-4", "my/path/file"]).code(3).stdout("");
         }
     }
 
@@ -1068,8 +1566,8 @@ mod test {
         #[test]
         fn disk() {
             pathmut(&["set", "C", "disk", "/path/to/file.txt"])
-                .success()
-                .stdout("/path/to/file.txt\n");
+                .code(4)
+                .stdout("");
             pathmut(&["set", "C", "disk", "C:\\path\\to\\file.txt"])
                 .success()
                 .stdout("C:\\path\\to\\file.txt\n");
@@ -1190,71 +1688,1457 @@ mod test {
     }
 
     #[test]
-    fn from_stdin() {
-        Command::cargo_bin("pathmut")
-            .unwrap()
-            .args(["get", "ext"])
-            .write_stdin("/my/path/file.txt")
-            .assert()
+    fn convert_mounts() {
+        pathmut(&["convert", "wsl", r"C:\Users\me", "c:"])
             .success()
-            .stdout("txt\n");
+            .stdout("/mnt/c/Users/me\n/mnt/c\n");
+        pathmut(&["convert", "msys", r"C:\Users\me"])
+            .success()
+            .stdout("/c/Users/me\n");
+        pathmut(&["convert", "cygwin", r"D:\Users\me"])
+            .success()
+            .stdout("/cygdrive/d/Users/me\n");
+        pathmut(&["convert", "wsl", "my/path"])
+            .success()
+            .stdout("my/path\n");
+        pathmut(&["convert", "wsl", "C:foo"])
+            .code(4)
+            .stderr("error: no mount path for drive-relative path 'C:foo'\n");
     }
 
     #[test]
-    fn help_default() {
-        pathmut(&[])
+    fn convert_uri() {
+        pathmut(&["convert", "uri", "/home/me/a b#1.txt", "/"])
+            .success()
+            .stdout("file:///home/me/a%20b%231.txt\nfile:///\n");
+        pathmut(&["convert", "uri", r"C:\Users\me\100%.txt", r"C:\"])
+            .success()
+            .stdout("file:///C:/Users/me/100%25.txt\nfile:///C:/\n");
+        pathmut(&["convert", "uri", r"\\server\share\a.txt"])
+            .success()
+            .stdout("file://server/share/a.txt\n");
+        pathmut(&["convert", "uri", "my/path"])
             .failure()
-            .stderr(predicate::str::contains("Print help information"));
+            .stderr("error: no file URI for relative path 'my/path'\n");
+        pathmut(&["convert", "uri", r"\\.\COM1"])
+            .code(4)
+            .stderr("error: no file URI for device path '\\\\.\\COM1'\n");
+        pathmut(&["convert", "uri", r"\\?\pipe"])
+            .code(4)
+            .stderr("error: no file URI for verbatim path '\\\\?\\pipe'\n");
     }
 
     #[test]
-    fn multiple_paths() {
-        pathmut(&["get", "ext", "file.txt", "another.png"])
-            .success()
-            .stdout("txt\npng\n");
-        pathmut(&["ext", "file.txt", "another.png"])
-            .success()
-            .stdout("txt\npng\n");
-        pathmut(&["get", "stem", "file.txt", "another.png"])
-            .success()
-            .stdout("file\nanother\n");
-        pathmut(&["stem", "file.txt", "another.png"])
+    fn file_uri_paths() {
+        pathmut(&["get", "name", "file:///home/me/a%20b.txt"])
             .success()
-            .stdout("file\nanother\n");
+            .stdout("a b.txt\n");
         pathmut(&[
-            "set",
-            "blah",
-            "stem",
-            "path/to/file.txt",
-            "just/another.png",
+            "convert",
+            "win",
+            "file:///c:/x",
+            "file:///C|/y",
+            "file:///C:",
         ])
         .success()
-        .stdout("path/to/blah.txt\njust/blah.png\n");
+        .stdout("c:\\x\nC:\\y\nC:\\\n");
+        pathmut(&["get", "disk", "file:///C:/x"])
+            .success()
+            .stdout("C\n");
+        pathmut(&["is", "windows", "file://server/share/a"]).success();
+        pathmut(&["get", "0", "file://server/share/a"])
+            .success()
+            .stdout("\\\\server\\share\n");
+        pathmut(&["is", "unix", "file:///etc/hosts", "file://localhost/etc"]).success();
+        pathmut(&["-u", "get", "parent", "file:///C:/x/y"])
+            .success()
+            .stdout("file:///C:/x\n");
+        pathmut(&["-u", "get", "name", "file:%41.txt"])
+            .success()
+            .stdout("file:%41.txt\n");
+        pathmut(&["set", "md", "ext", "file:report%20v2.txt", "file:name"])
+            .success()
+            .stdout("file:report%20v2.md\nfile:name.md\n");
+        pathmut(&["normalize", "file:name"])
+            .success()
+            .stdout("file:name\n");
     }
 
     #[test]
-    fn windows() {
-        pathmut(&["get", "ext", r"C:\Users\username\file.txt"])
+    fn convert_from_mounts() {
+        pathmut(&[
+            "convert",
+            "win",
+            "--from",
+            "wsl",
+            "/mnt/c/Users/me",
+            "/mnt/d",
+        ])
+        .success()
+        .stdout("C:\\Users\\me\nD:\\\n");
+        pathmut(&["convert", "win", "--from", "msys", "/c/Users/me"])
             .success()
-            .stdout("txt\n");
-        pathmut(&["get", "stem", r"C:\Users\username\file.txt"])
+            .stdout("C:\\Users\\me\n");
+        pathmut(&[
+            "convert",
+            "win",
+            "--from",
+            "cygwin",
+            "/cygdrive/c/x",
+            "/home/x",
+        ])
+        .success()
+        .stdout("C:\\x\n\\home\\x\n");
+        pathmut(&["convert", "wsl", "--from", "msys", "/c/x"])
             .success()
-            .stdout("file\n");
-        pathmut(&["get", "name", r"C:\Users\username\file.txt"])
+            .stdout("/mnt/c/x\n");
+        pathmut(&["convert", "win", "/mnt/c/x"])
             .success()
-            .stdout("file.txt\n");
+            .stdout("\\mnt\\c\\x\n");
     }
 
-    /*
-    #[test]
-    fn piped_multiple_paths() {
-        Command::cargo_bin("pathmut")
-            .unwrap()
-            .args(&["ext"])
-            .write_stdin("/my/path/file.txt\npicture.png")
-            .assert()
-            .success()
-            .stdout("txt\npng\n");
+    mod common {
+        use super::*;
+
+        #[test]
+        fn unix() {
+            pathmut(&["common", "/a/b/c", "/a/b/d/e", "/a/bb"])
+                .success()
+                .stdout("/a\n");
+            pathmut(&["common", "/a/b"]).success().stdout("/a/b\n");
+            pathmut(&["common", "a/b", "a/c"]).success().stdout("a\n");
+            pathmut(&["common", "a", "b"]).success().stdout(".\n");
+        }
+
+        #[test]
+        fn same_paths() {
+            pathmut(&["common", "/a/file.txt"])
+                .success()
+                .stdout("/a/file.txt\n");
+            pathmut(&["common", "/a/file.txt", "/a/file.txt"])
+                .success()
+                .stdout("/a/file.txt\n");
+        }
+
+        #[test]
+        fn no_paths() {
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .arg("common")
+                .write_stdin("")
+                .assert()
+                .success()
+                .stdout("")
+                .stderr("");
+        }
+
+        #[test]
+        fn normalized() {
+            pathmut(&["common", "/a/x/../b/c", "/a/b/d"])
+                .success()
+                .stdout("/a\n");
+            pathmut(&["common", "-n", "/a/x/../b/c", "/a/b/d"])
+                .success()
+                .stdout("/a/b\n");
+        }
+
+        #[test]
+        fn windows() {
+            pathmut(&["common", r"C:\a\b", r"c:\a\c"])
+                .success()
+                .stdout("C:\\a\n");
+            pathmut(&["common", r"C:\a", r"D:\a"])
+                .failure()
+                .stderr("error: paths have no common ancestor\n");
+        }
+
+        #[test]
+        fn none() {
+            pathmut(&["common", "/a", "a"]).failure();
+            pathmut(&["common", "/a", r"C:\a"]).failure();
+        }
+    }
+
+    mod strip_prefix {
+        use super::*;
+
+        #[test]
+        fn unix() {
+            pathmut(&["strip-prefix", "/src/project", "/src/project/lib/a.rs"])
+                .success()
+                .stdout("lib/a.rs\n");
+            pathmut(&["strip-prefix", "/src/project", "/src/project"])
+                .success()
+                .stdout(".\n");
+            pathmut(&["strip-prefix", "-n", "/a/./b", "/a/b/c"])
+                .success()
+                .stdout("c\n");
+        }
+
+        #[test]
+        fn windows() {
+            pathmut(&["strip-prefix", r"c:\src", r"C:\src\lib\a.rs"])
+                .success()
+                .stdout("lib\\a.rs\n");
+        }
+
+        #[test]
+        fn not_under() {
+            pathmut(&[
+                "strip-prefix",
+                "/src/project",
+                "/src/projectx/a",
+                "/src/project/b",
+            ])
+            .failure()
+            .stdout("b\n")
+            .stderr("error: '/src/project' doesn't contain '/src/projectx/a'\n");
+            pathmut(&["strip-prefix", "--pass", "/src/project", "/src/projectx/a"])
+                .success()
+                .stdout("/src/projectx/a\n");
+        }
+    }
+
+    mod rebase {
+        use super::*;
+
+        #[test]
+        fn unix() {
+            pathmut(&[
+                "rebase",
+                "/src/project",
+                "/build/out",
+                "/src/project/lib/a.rs",
+            ])
+            .success()
+            .stdout("/build/out/lib/a.rs\n");
+            pathmut(&["rebase", "/a", "/b", "/a"])
+                .success()
+                .stdout("/b\n");
+        }
+
+        #[test]
+        fn between_types() {
+            pathmut(&["rebase", "/src", r"C:\out", "/src/lib/a.rs"])
+                .success()
+                .stdout("C:\\out\\lib\\a.rs\n");
+            pathmut(&["rebase", r"C:\src", "/mnt/src", r"C:\src\lib\a.rs"])
+                .success()
+                .stdout("/mnt/src/lib/a.rs\n");
+        }
+
+        #[test]
+        fn not_under() {
+            pathmut(&["rebase", "/src", "/out", "/other/a"]).failure();
+            pathmut(&["rebase", "--pass", "/src", "/out", "/other/a", "/src/a"])
+                .success()
+                .stdout("/other/a\n/out/a\n");
+        }
+    }
+
+    mod sanitize {
+        use super::*;
+
+        #[test]
+        fn forbidden() {
+            pathmut(&["sanitize", "uploads/Report: Q3 <final>?.pdf"])
+                .success()
+                .stdout("uploads/Report_ Q3 _final__.pdf\n");
+            pathmut(&["sanitize", "-r", "", "a/b?c*.txt"])
+                .success()
+                .stdout("a/bc.txt\n");
+            pathmut(&["sanitize", "--target", "unix", "a/b:c?"])
+                .success()
+                .stdout("a/b:c?\n");
+            pathmut(&["sanitize", "-r", "?", "a"]).failure();
+        }
+
+        #[test]
+        fn whole_string() {
+            pathmut(&["sanitize", "-c", "..", "Report: Q3/Q4 <final>?.pdf"])
+                .success()
+                .stdout("Report_ Q3_Q4 _final__.pdf\n");
+        }
+
+        #[test]
+        fn reserved() {
+            pathmut(&["sanitize", "a/CON.txt", "a/nul", "a/lpt1 .md"])
+                .success()
+                .stdout("a/CON_.txt\na/nul_\na/lpt1 _.md\n");
+            pathmut(&["sanitize", "-c", "stem", "a/aux.txt"])
+                .success()
+                .stdout("a/aux_.txt\n");
+        }
+
+        #[test]
+        fn trailing() {
+            pathmut(&["sanitize", "a/b. . ", "a/..."])
+                .success()
+                .stdout("a/b\na/_\n");
+        }
+
+        #[test]
+        fn length() {
+            let long = format!("{}.pdf", "a".repeat(300));
+            let cut = format!("{}.pdf\n", "a".repeat(251));
+            pathmut(&["sanitize", &long]).success().stdout(cut);
+            pathmut(&["sanitize", "--max-length", "10", "abcdefghijkl.pdf"])
+                .success()
+                .stdout("abcdef.pdf\n");
+            pathmut(&["sanitize", "--max-length", "10", "ééééééé.txt"])
+                .success()
+                .stdout("ééé.txt\n");
+            pathmut(&["sanitize", "--max-length", "0", "x"]).code(2);
+        }
+    }
+
+    mod uri {
+        use super::*;
+
+        const URI: &str = "https://me:pw@example.com:8080/a/b.tar.gz?x=1&y=2#top";
+
+        #[test]
+        fn get() {
+            let parts = [
+                ("scheme", "https"),
+                ("user", "me"),
+                ("password", "pw"),
+                ("host", "example.com"),
+                ("port", "8080"),
+                ("route", "/a/b.tar.gz"),
+                ("query", "x=1&y=2"),
+                ("fragment", "top"),
+                ("ext", "gz"),
+                ("name", "b.tar.gz"),
+                ("parent", "/a"),
+                ("1", "a"),
+            ];
+            for (component, value) in parts {
+                pathmut(&["--as-uri", "get", component, URI])
+                    .success()
+                    .stdout(format!("{value}\n"));
+            }
+            pathmut(&["--as-uri", "get", "host", "http://[::1]:80/"])
+                .success()
+                .stdout("[::1]\n");
+        }
+
+        #[test]
+        fn set() {
+            pathmut(&["--as-uri", "set", "http", "scheme", URI])
+                .success()
+                .stdout("http://me:pw@example.com:8080/a/b.tar.gz?x=1&y=2#top\n");
+            pathmut(&["--as-uri", "set", "other.org", "host", URI])
+                .success()
+                .stdout("https://me:pw@other.org:8080/a/b.tar.gz?x=1&y=2#top\n");
+            pathmut(&["--as-uri", "set", "zip", "ext", URI])
+                .success()
+                .stdout("https://me:pw@example.com:8080/a/b.tar.zip?x=1&y=2#top\n");
+            pathmut(&["--as-uri", "set", "c", "name", "https://x.org"])
+                .success()
+                .stdout("https://x.org/c\n");
+            pathmut(&["--as-uri", "set", "x.org", "host", "a/b"])
+                .success()
+                .stdout("//x.org/a/b\n");
+        }
+
+        #[test]
+        fn delete() {
+            pathmut(&["--as-uri", "delete", "user", URI])
+                .success()
+                .stdout("https://example.com:8080/a/b.tar.gz?x=1&y=2#top\n");
+            pathmut(&["--as-uri", "delete", "port", URI])
+                .success()
+                .stdout("https://me:pw@example.com/a/b.tar.gz?x=1&y=2#top\n");
+            pathmut(&["--as-uri", "delete", "query", URI])
+                .success()
+                .stdout("https://me:pw@example.com:8080/a/b.tar.gz#top\n");
+            pathmut(&["--as-uri", "delete", "name", "https://x.org/a/b?q"])
+                .success()
+                .stdout("https://x.org/a?q\n");
+        }
+
+        #[test]
+        fn replace() {
+            pathmut(&["--as-uri", "replace", "9090", "port", URI, "https://x.org/"])
+                .success()
+                .stdout(
+                    "https://me:pw@example.com:9090/a/b.tar.gz?x=1&y=2#top\n\
+                    https://x.org/\n",
+                );
+        }
+
+        #[test]
+        fn has() {
+            pathmut(&["--as-uri", "has", "port", URI]).success();
+            pathmut(&["--as-uri", "has", "port", "https://x.org/"]).failure();
+            pathmut(&["--as-uri", "has", "fragment", "--all", URI, "x#y"]).success();
+        }
+
+        const QUERY: &str = "https://x.org/?page=2&q=a+b%21&tag=1&tag=2&utm_source=tw&debug";
+
+        #[test]
+        fn get_query() {
+            let params = [
+                ("query:page", "2"),
+                ("query:q", "a b!"),
+                ("query:tag", "1"),
+                ("query:tag:1", "2"),
+                ("query:tag:-1", "2"),
+                ("query:debug", ""),
+                ("keys", "page&q&tag&utm_source&debug"),
+            ];
+            for (component, value) in params {
+                pathmut(&["--as-uri", "get", component, QUERY])
+                    .success()
+                    .stdout(format!("{value}\n"));
+            }
+        }
+
+        #[test]
+        fn encoded_keys() {
+            let uri = "https://x/?a%20b=1&a+b=2&c";
+            pathmut(&["--as-uri", "get", "keys", uri])
+                .success()
+                .stdout("a b&c\n");
+            pathmut(&["--as-uri", "get", "query:a b", uri])
+                .success()
+                .stdout("1\n");
+        }
+
+        #[test]
+        fn set_query() {
+            pathmut(&["--as-uri", "set", "3", "query:page", QUERY])
+                .success()
+                .stdout("https://x.org/?page=3&q=a+b%21&tag=1&tag=2&utm_source=tw&debug\n");
+            pathmut(&["--as-uri", "set", "a b&c", "query:tag", QUERY])
+                .success()
+                .stdout("https://x.org/?page=2&q=a+b%21&tag=a%20b%26c&utm_source=tw&debug\n");
+            pathmut(&["--as-uri", "set", "3", "query:tag:1", QUERY])
+                .success()
+                .stdout("https://x.org/?page=2&q=a+b%21&tag=1&tag=3&utm_source=tw&debug\n");
+            pathmut(&["--as-uri", "set", "1", "query:page", "https://x.org/?#top"])
+                .success()
+                .stdout("https://x.org/?page=1#top\n");
+        }
+
+        #[test]
+        fn delete_query() {
+            pathmut(&["--as-uri", "delete", "query:utm_source", QUERY])
+                .success()
+                .stdout("https://x.org/?page=2&q=a+b%21&tag=1&tag=2&debug\n");
+            pathmut(&["--as-uri", "delete", "query:tag", QUERY])
+                .success()
+                .stdout("https://x.org/?page=2&q=a+b%21&utm_source=tw&debug\n");
+            pathmut(&["--as-uri", "delete", "query:tag:0", QUERY])
+                .success()
+                .stdout("https://x.org/?page=2&q=a+b%21&tag=2&utm_source=tw&debug\n");
+            pathmut(&["--as-uri", "delete", "query:a", "https://x.org/?a=1#top"])
+                .success()
+                .stdout("https://x.org/#top\n");
+        }
+
+        #[test]
+        fn has_query() {
+            pathmut(&["--as-uri", "has", "query:debug", QUERY]).success();
+            pathmut(&["--as-uri", "has", "query:tag:2", QUERY]).failure();
+        }
+
+        #[test]
+        fn other_commands() {
+            pathmut(&["--as-uri", "depth", URI]).failure();
+            pathmut(&["--as-uri", "is", "absolute", URI]).failure();
+        }
+
+        #[test]
+        fn normalize() {
+            pathmut(&[
+                "--as-uri",
+                "normalize",
+                "HTTP://Me@WWW.Example.COM:80/a/./b/../c/%7euser/%2fx?q=%3d#f%7E",
+                "https://x.org:443",
+                "mailto:Me@Example.com",
+            ])
+            .success()
+            .stdout(
+                "http://Me@www.example.com/a/c/~user/%2Fx?q=%3D#f~\n\
+                https://x.org/\n\
+                mailto:Me@Example.com\n",
+            );
+            pathmut(&["--as-uri", "-n", "get", "port", "http://x.org:80/"])
+                .success()
+                .stdout("\n");
+            pathmut(&["--as-uri", "-n", "get", "parent", "http://x.org/a/../b/c"])
+                .success()
+                .stdout("/b\n");
+        }
+
+        #[test]
+        fn resolve() {
+            // examples from RFC 3986 section 5.4
+            let base = "http://a/b/c/d;p?q";
+            let examples = [
+                ("g:h", "g:h"),
+                ("g", "http://a/b/c/g"),
+                ("./g", "http://a/b/c/g"),
+                ("g/", "http://a/b/c/g/"),
+                ("/g", "http://a/g"),
+                ("//g", "http://g"),
+                ("?y", "http://a/b/c/d;p?y"),
+                ("g?y", "http://a/b/c/g?y"),
+                ("#s", "http://a/b/c/d;p?q#s"),
+                ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+                ("", "http://a/b/c/d;p?q"),
+                (".", "http://a/b/c/"),
+                ("..", "http://a/b/"),
+                ("../g", "http://a/b/g"),
+                ("../../g", "http://a/g"),
+                ("../../../g", "http://a/g"),
+                ("/./g", "http://a/g"),
+                ("g..", "http://a/b/c/g.."),
+                ("./g/.", "http://a/b/c/g/"),
+                ("g;x=1/../y", "http://a/b/c/y"),
+                ("g?y/../x", "http://a/b/c/g?y/../x"),
+                ("g#s/../x", "http://a/b/c/g#s/../x"),
+                ("http:g", "http:g"),
+            ];
+            for (reference, target) in examples {
+                pathmut(&["resolve", base, reference])
+                    .success()
+                    .stdout(format!("{target}\n"));
+            }
+        }
+
+        #[test]
+        fn resolve_normalized() {
+            pathmut(&["resolve", "-n", "HTTP://X.org:80/a/", "b/%7e"])
+                .success()
+                .stdout("http://x.org/a/b/~\n");
+            pathmut(&["-n", "resolve", "http://a/b/c", "../d?y"])
+                .success()
+                .stdout("http://a/d?y\n");
+        }
+
+        #[test]
+        fn normalize_relative_reference() {
+            pathmut(&["--as-uri", "normalize", "../a/./b"])
+                .success()
+                .stdout("../a/./b\n");
+            pathmut(&["--as-uri", "normalize", "/../a/./b"])
+                .success()
+                .stdout("/a/b\n");
+            pathmut(&["--as-uri", "-n", "get", "parent", "../a/b"])
+                .success()
+                .stdout("../a\n");
+        }
+    }
+
+    mod errors {
+        use super::*;
+
+        #[test]
+        fn out_of_range() {
+            pathmut(&["set", "x", "5", "a/b"])
+                .code(3)
+                .stderr("error: component 5 is out of range for 'a/b'\n");
+            pathmut(&["set", "c", "2", "a/b"])
+                .success()
+                .stdout("a/b/c\n");
+            pathmut(&["delete", "-3", "a/b"]).success().stdout("a/b\n");
+            pathmut(&["replace", "x", "5", "a/b"])
+                .success()
+                .stdout("a/b\n");
+        }
+
+        #[test]
+        fn not_applicable() {
+            pathmut(&["set", "C", "disk", "/unix/path"])
+                .code(4)
+                .stderr("error: disk doesn't apply to '/unix/path'\n");
+            pathmut(&["get", "host", "/unix/path"]).code(4);
+            pathmut(&["replace", "C", "disk", "/unix/path"])
+                .success()
+                .stdout("/unix/path\n");
+            pathmut(&["has", "disk", "/unix/path"]).code(1);
+        }
+
+        #[test]
+        fn uri_and_git() {
+            pathmut(&["--as-uri", "get", "disk", "https://x/a"])
+                .code(4)
+                .stderr("error: disk doesn't apply to 'https://x/a'\n");
+            pathmut(&["--as-uri", "set", "x", "5", "https://x/a"])
+                .code(3)
+                .stderr("error: component 5 is out of range for 'https://x/a'\n");
+            pathmut(&["--as-uri", "set", "3", "query:page:4", "https://x/?page=1"])
+                .code(3)
+                .stderr("error: component query:page:4 is out of range for 'https://x/?page=1'\n");
+            pathmut(&["--as-uri", "get", "query:page:-2", "https://x/?page=1"]).code(3);
+            pathmut(&["--as-uri", "get", "query:x:9", "https://x/?page=1"])
+                .code(4)
+                .stderr("error: query:x:9 doesn't apply to 'https://x/?page=1'\n");
+            pathmut(&["--as-uri", "get", "query:x", "https://x/?page=1"]).code(4);
+            pathmut(&["--as-uri", "set", "1", "query:x:0", "https://x/?page=1"]).code(4);
+            pathmut(&["--as-uri", "set", "1", "query:x", "https://x/?page=1"])
+                .success()
+                .stdout("https://x/?page=1&x=1\n");
+            pathmut(&[
+                "--as-uri",
+                "replace",
+                "1",
+                "query:page:4",
+                "https://x/?page=1",
+            ])
+            .success()
+            .stdout("https://x/?page=1\n");
+            pathmut(&["--as-git", "get", "query", "git@h:o/r"])
+                .code(4)
+                .stderr("error: query doesn't apply to 'git@h:o/r'\n");
+            pathmut(&["--as-git", "set", "C", "disk", "git@h:o/r"]).code(4);
+            pathmut(&["--as-git", "replace", "q", "query", "git@h:o/r"])
+                .success()
+                .stdout("git@h:o/r\n");
+            pathmut(&["--as-uri", "delete", "disk", "https://x/a"])
+                .success()
+                .stdout("https://x/a\n");
+        }
+
+        #[test]
+        fn invalid_value() {
+            pathmut(&["set", "é", "disk", "C:\\path"])
+                .code(5)
+                .stderr("error: 'é' isn't a valid disk for 'C:\\path'\n");
+            pathmut(&["set", "CD", "disk", "C:\\path"]).code(5);
+            pathmut(&["set", "a.b", "ext:0", "file.txt"]).code(5);
+        }
+
+        #[test]
+        fn other_paths_continue() {
+            pathmut(&["get", "2", "a", "a/b/c", "/x"])
+                .code(3)
+                .stdout("c\n")
+                .stderr("error: component 2 is out of range for 'a'\nerror: component 2 is out of range for '/x'\n");
+            pathmut(&["-w", "set", "D", "disk", "C:\\a"])
+                .success()
+                .stdout("D:\\a\n");
+        }
+    }
+
+    mod git {
+        use super::*;
+
+        const SCP: &str = "git@github.com:rutrum/pathmut.git";
+        const SSH: &str = "ssh://git@example.com:2222/group/sub/project.git";
+        const HTTPS: &str = "https://github.com/rutrum/pathmut";
+
+        #[test]
+        fn get() {
+            let parts = [
+                (SCP, "user", "git"),
+                (SCP, "host", "github.com"),
+                (SCP, "port", ""),
+                (SCP, "scheme", ""),
+                (SCP, "owner", "rutrum"),
+                (SCP, "repo", "pathmut"),
+                (SCP, "route", "rutrum/pathmut.git"),
+                (SCP, "name", "pathmut.git"),
+                (SSH, "scheme", "ssh"),
+                (SSH, "port", "2222"),
+                (SSH, "owner", "group/sub"),
+                (SSH, "repo", "project"),
+                (SSH, "0", "group"),
+                (HTTPS, "user", ""),
+                (HTTPS, "owner", "rutrum"),
+                (HTTPS, "repo", "pathmut"),
+            ];
+            for (remote, component, value) in parts {
+                pathmut(&["--as-git", "get", component, remote])
+                    .success()
+                    .stdout(format!("{value}\n"));
+            }
+            pathmut(&["--as-git", "get", "owner", "server:/srv/git/repo.git"])
+                .success()
+                .stdout("/srv/git\n");
+        }
+
+        #[test]
+        fn set() {
+            pathmut(&["--as-git", "set", "fork", "repo", SCP, HTTPS])
+                .success()
+                .stdout("git@github.com:rutrum/fork.git\nhttps://github.com/rutrum/fork\n");
+            pathmut(&["--as-git", "set", "me", "owner", SCP])
+                .success()
+                .stdout("git@github.com:me/pathmut.git\n");
+            pathmut(&["--as-git", "set", "gitlab.com", "host", SSH])
+                .success()
+                .stdout("ssh://git@gitlab.com:2222/group/sub/project.git\n");
+            pathmut(&["--as-git", "set", "me", "user", HTTPS])
+                .success()
+                .stdout("https://me@github.com/rutrum/pathmut\n");
+            pathmut(&["--as-git", "set", "2222", "port", SCP])
+                .success()
+                .stdout("ssh://git@github.com:2222/rutrum/pathmut.git\n");
+            pathmut(&["--as-git", "replace", "me", "user", HTTPS])
+                .success()
+                .stdout(format!("{HTTPS}\n"));
+        }
+
+        #[test]
+        fn delete() {
+            pathmut(&["--as-git", "delete", "owner", SSH])
+                .success()
+                .stdout("ssh://git@example.com:2222/project.git\n");
+            pathmut(&["--as-git", "delete", "port", SSH])
+                .success()
+                .stdout("ssh://git@example.com/group/sub/project.git\n");
+            pathmut(&["--as-git", "delete", "repo", SCP])
+                .success()
+                .stdout(format!("{SCP}\n"));
+        }
+
+        #[test]
+        fn has() {
+            pathmut(&["--as-git", "has", "user", SCP]).success();
+            pathmut(&["--as-git", "has", "user", HTTPS]).failure();
+            pathmut(&["--as-git", "has", "-p", "port", SSH, SCP])
+                .success()
+                .stdout("true\n");
+        }
+
+        #[test]
+        fn convert() {
+            pathmut(&["convert", "https", SCP, SSH]).success().stdout(
+                "https://github.com/rutrum/pathmut\nhttps://example.com/group/sub/project\n",
+            );
+            pathmut(&["convert", "scp", HTTPS, SSH]).success().stdout(
+                "git@github.com:rutrum/pathmut.git\ngit@example.com:group/sub/project.git\n",
+            );
+            pathmut(&["--as-git", "convert", "ssh", HTTPS, SCP])
+                .success()
+                .stdout(
+                    "ssh://git@github.com/rutrum/pathmut.git\nssh://git@github.com/rutrum/pathmut.git\n",
+                );
+            pathmut(&["--as-git", "convert", "unix", SCP]).failure();
+        }
+
+        #[test]
+        fn not_remotes() {
+            pathmut(&["--as-git", "get", "repo", "/local/repo", SCP])
+                .failure()
+                .stdout("pathmut\n")
+                .stderr("error: not a git remote '/local/repo'\n");
+            pathmut(&["convert", "https", "C:/repo"]).failure();
+            pathmut(&["--as-git", "depth", SCP])
+                .failure()
+                .stderr(predicate::str::contains(
+                    "'depth' doesn't work with --as-git",
+                ));
+        }
+    }
+
+    mod relative {
+        use super::*;
+
+        #[test]
+        fn unix() {
+            pathmut(&[
+                "relative",
+                "--from",
+                "/site/blog/post",
+                "/site/assets/logo.png",
+            ])
+            .success()
+            .stdout("../../assets/logo.png\n");
+            pathmut(&["relative", "--from", "/a", "/a/b/c", "/a", "/"])
+                .success()
+                .stdout("b/c\n.\n..\n");
+            pathmut(&["relative", "--from", "a/b", "a/c"])
+                .success()
+                .stdout("../c\n");
+        }
+
+        #[test]
+        fn lexical() {
+            pathmut(&["relative", "--from", "a/d/..", "a/./b/../c"])
+                .success()
+                .stdout("c\n");
+            pathmut(&["relative", "--from", "../x/z", "../x/y"])
+                .success()
+                .stdout("../y\n");
+        }
+
+        #[test]
+        fn windows() {
+            pathmut(&["relative", "--from", r"C:\a\c", r"C:\a\b"])
+                .success()
+                .stdout("..\\b\n");
+            pathmut(&["relative", "--from", r"c:\a", r"C:\a\b"])
+                .success()
+                .stdout("b\n");
+            pathmut(&["-u", "relative", "--from", r"C:\a\c", r"C:\a\b"])
+                .success()
+                .stdout("../C:\\a\\b\n");
+        }
+
+        #[test]
+        fn impossible() {
+            pathmut(&["relative", "--from", r"D:\a", r"C:\a\b"])
+                .failure()
+                .stderr("error: no relative path from 'D:\\a' to 'C:\\a\\b'\n");
+            pathmut(&["relative", "--from", "/a", "a/b", "/a/c"])
+                .failure()
+                .stdout("c\n");
+            pathmut(&["relative", "--from", "../a", "b"]).failure();
+            pathmut(&["relative", "--from", "/a", r"C:\a"]).failure();
+        }
+    }
+
+    mod join {
+        use super::*;
+
+        #[test]
+        fn paths() {
+            pathmut(&["join", "my", "path", "to/file.txt"])
+                .success()
+                .stdout("my/path/to/file.txt\n");
+            pathmut(&["join", "my/path"]).success().stdout("my/path\n");
+            pathmut(&["join", r"C:\my", "path"])
+                .success()
+                .stdout("C:\\my\\path\n");
+        }
+
+        #[test]
+        fn absolute() {
+            pathmut(&["join", "my", "/path", "file.txt"])
+                .success()
+                .stdout("/path/file.txt\n");
+            pathmut(&["join", "--strict", "my", "/path", "file.txt"])
+                .code(4)
+                .stdout("")
+                .stderr("error: cannot join absolute path '/path'\n");
+        }
+
+        #[test]
+        fn normalize() {
+            pathmut(&["join", "-n", "my/path", "../other", "./file.txt"])
+                .success()
+                .stdout("my/other/file.txt\n");
+        }
+
+        #[test]
+        fn each() {
+            pathmut(&["join", "--each", "base", "a", "b/c"])
+                .success()
+                .stdout("base/a\nbase/b/c\n");
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(["join", "--each", "--strict", "base"])
+                .write_stdin("a\n/b\nc\n")
+                .assert()
+                .code(4)
+                .stdout("base/a\nbase/c\n")
+                .stderr("error: cannot join absolute path '/b'\n");
+        }
+
+        #[test]
+        fn json() {
+            pathmut(&["--output", "jsonl", "join", "my", "path"])
+                .success()
+                .stdout("{\"input\":[\"my\",\"path\"],\"output\":\"my/path\"}\n");
+        }
+    }
+
+    mod range {
+        use super::*;
+
+        #[test]
+        fn get() {
+            pathmut(&["get", "1..3", "/my/path/to/file.txt"])
+                .success()
+                .stdout("my/path\n");
+            pathmut(&["get", "..-1", "/my/path/to/file.txt"])
+                .success()
+                .stdout("/my/path/to\n");
+            pathmut(&["get", "2..", "my/path/to/file.txt"])
+                .success()
+                .stdout("to/file.txt\n");
+            pathmut(&["get", "-3..", "/my/path/to/file.txt"])
+                .success()
+                .stdout("path/to/file.txt\n");
+            pathmut(&["get", "..", "/my/path"])
+                .success()
+                .stdout("/my/path\n");
+            pathmut(&["get", "0..2", r"C:\my\path"])
+                .success()
+                .stdout("C:\\\n");
+        }
+
+        #[test]
+        fn outside() {
+            pathmut(&["get", "5..9", "/my/path"]).success().stdout("\n");
+            pathmut(&["get", "2..1", "/my/path"]).success().stdout("\n");
+            pathmut(&["get", "-9..2", "/my/path"])
+                .success()
+                .stdout("/my\n");
+            pathmut(&["has", "5..", "/my/path"]).failure();
+        }
+
+        #[test]
+        fn delete() {
+            pathmut(&["delete", "1..3", "/my/path/to/file.txt"])
+                .success()
+                .stdout("/to/file.txt\n");
+            pathmut(&["delete", "..2", "my/path/to/file.txt"])
+                .success()
+                .stdout("to/file.txt\n");
+            pathmut(&["delete", "-2..", "my/path/to/file.txt"])
+                .success()
+                .stdout("my/path\n");
+        }
+
+        #[test]
+        fn set() {
+            pathmut(&["set", "new/dir", "1..3", "/my/path/to/file.txt"])
+                .success()
+                .stdout("/new/dir/to/file.txt\n");
+            pathmut(&["set", "/build/out", "..3", "/src/project/lib/a.rs"])
+                .success()
+                .stdout("/build/out/lib/a.rs\n");
+            pathmut(&["set", "new", "2..2", "my/path/file.txt"])
+                .success()
+                .stdout("my/path/new/file.txt\n");
+            pathmut(&["replace", "new", "5..", "my/path/file.txt"])
+                .success()
+                .stdout("my/path/file.txt\n");
+        }
+
+        #[test]
+        fn invalid() {
+            pathmut(&["get", "1..x", "/my/path"]).failure();
+            pathmut(&["get", "1...3", "/my/path"]).failure();
+        }
+    }
+
+    mod extensions {
+        use super::*;
+
+        #[test]
+        fn exts() {
+            pathmut(&["get", "exts", "archive.tar.gz", "v1.2.3.txt", ".bashrc"])
+                .success()
+                .stdout("tar.gz\n2.3.txt\n\n");
+            pathmut(&["set", "zip", "exts", "dir/archive.tar.gz"])
+                .success()
+                .stdout("dir/archive.zip\n");
+            pathmut(&["delete", "exts", "dir/archive.tar.gz"])
+                .success()
+                .stdout("dir/archive\n");
+            pathmut(&["replace", "zip", "exts", "dir/archive"])
+                .success()
+                .stdout("dir/archive\n");
+        }
+
+        #[test]
+        fn nth() {
+            pathmut(&["get", "ext:0", "archive.tar.gz"])
+                .success()
+                .stdout("tar\n");
+            pathmut(&["get", "ext:-1", "archive.tar.gz"])
+                .success()
+                .stdout("gz\n");
+            pathmut(&["get", "ext:2", "archive.tar.gz"])
+                .code(3)
+                .stderr("error: component ext:2 is out of range for 'archive.tar.gz'\n");
+            pathmut(&["set", "bz2", "ext:1", "archive.tar.gz"])
+                .success()
+                .stdout("archive.tar.bz2\n");
+            pathmut(&["set", "sig", "ext:2", "archive.tar.gz"])
+                .success()
+                .stdout("archive.tar.gz.sig\n");
+            pathmut(&["delete", "ext:0", "archive.tar.gz"])
+                .success()
+                .stdout("archive.gz\n");
+            pathmut(&["has", "ext:-3", "archive.tar.gz"]).failure();
+        }
+
+        #[test]
+        fn nth_without_name() {
+            for path in ["/", "", ".", "..", "C:", "\\\\?\\"] {
+                pathmut(&["get", "ext:0", path]).code(3);
+                pathmut(&["get", "ext:-1", path]).code(3);
+                pathmut(&["has", "ext:0", path]).code(1);
+                pathmut(&["set", "md", "ext:0", path]).code(4);
+                pathmut(&["set", "md", "ext", path]).code(4);
+                pathmut(&["set", "md", "exts", path]).code(4);
+                pathmut(&["set", "--compound", "md", "ext", path]).code(4);
+                pathmut(&["replace", "md", "ext", path]).success();
+                pathmut(&["delete", "ext:-1", path]).success();
+            }
+            pathmut(&["format", "{ext:0}", "/"]).success().stdout("\n");
+            pathmut(&["sanitize", "-c", "ext:0", "/"])
+                .success()
+                .stdout("/\n");
+        }
+
+        #[test]
+        fn compound() {
+            pathmut(&["get", "--compound", "ext", "archive.tar.gz"])
+                .success()
+                .stdout("tar.gz\n");
+            pathmut(&[
+                "ext",
+                "--compound",
+                "jquery.min.js",
+                "types.d.ts",
+                "v1.2.3.txt",
+            ])
+            .success()
+            .stdout("min.js\nd.ts\ntxt\n");
+            pathmut(&["set", "--compound", "tar.zst", "ext", "dir/archive.tar.gz"])
+                .success()
+                .stdout("dir/archive.tar.zst\n");
+            pathmut(&["delete", "--compound", "ext", "dir/archive.tar.gz"])
+                .success()
+                .stdout("dir/archive\n");
+            pathmut(&["replace", "--compound", "zip", "ext", "dir/archive"])
+                .success()
+                .stdout("dir/archive\n");
+            pathmut(&["has", "--compound", "ext", ".tar.gz"]).success();
+        }
+    }
+
+    mod info {
+        use super::*;
+
+        #[test]
+        fn unix() {
+            pathmut(&["info", "/my/path/file.tar.gz"]).success().stdout(
+                "/my/path/file.tar.gz\n\
+                    \x20     type: unix\n\
+                    \x20 absolute: true\n\
+                    normalized: true\n\
+                    \x20     disk: \n\
+                    \x20   parent: /my/path\n\
+                    \x20     name: file.tar.gz\n\
+                    \x20   prefix: file\n\
+                    \x20     stem: file.tar\n\
+                    \x20extension: gz\n\
+                    components:\n\
+                    \x20     0  -4  /\n\
+                    \x20     1  -3  my\n\
+                    \x20     2  -2  path\n\
+                    \x20     3  -1  file.tar.gz\n",
+            );
+        }
+
+        #[test]
+        fn windows() {
+            pathmut(&["info", r"C:\my\..\file.txt"])
+                .success()
+                .stdout(predicate::str::contains("type: windows, disk prefix\n"))
+                .stdout(predicate::str::contains("normalized: false\n"))
+                .stdout(predicate::str::contains("disk: C\n"))
+                .stdout(predicate::str::contains("      0  -5  C:\n"))
+                .stdout(predicate::str::contains("      3  -2  ..\n"));
+        }
+
+        #[test]
+        fn verbatim_disk() {
+            pathmut(&["info", r"\\?\C:\x"])
+                .success()
+                .stdout(predicate::str::contains(
+                    "type: windows, verbatim disk prefix\n",
+                ))
+                .stdout(predicate::str::contains("disk: C\n"));
+            pathmut(&["set", "D", "disk", r"\\?\C:\x"])
+                .success()
+                .stdout("\\\\?\\D:\\x\n");
+            pathmut(&["has", "disk", r"\\?\C:\x"]).success();
+        }
+
+        #[test]
+        fn prefixes() {
+            pathmut(&["info", r"\\server\share\file.txt"])
+                .success()
+                .stdout(predicate::str::contains("type: windows, unc prefix\n"))
+                .stdout(predicate::str::contains("      0  -3  \\\\server\\share\n"));
+            pathmut(&["info", r"\\?\C:\file.txt"])
+                .success()
+                .stdout(predicate::str::contains(
+                    "type: windows, verbatim disk prefix\n",
+                ));
+            pathmut(&["info", r"\\?\UNC\server\share"])
+                .success()
+                .stdout(predicate::str::contains(
+                    "type: windows, verbatim unc prefix\n",
+                ));
+            pathmut(&["info", r"\\.\COM1"])
+                .success()
+                .stdout(predicate::str::contains("type: windows, device prefix\n"));
+        }
+
+        #[test]
+        fn relative() {
+            pathmut(&["info", "./my/../file"])
+                .success()
+                .stdout(predicate::str::contains("absolute: false\n"))
+                .stdout(predicate::str::contains("      0  -4  .\n"));
+        }
+
+        #[test]
+        fn json() {
+            pathmut(&["--output", "jsonl", "info", "/my/file.txt"])
+                .success()
+                .stdout(
+                    r#"{"input":"/my/file.txt","type":"unix","windows_prefix":null,"absolute":true,"normalized":true,"disk":"","parent":"/my","name":"file.txt","prefix":"file","stem":"file","extension":"txt","components":["/","my","file.txt"]}"#
+                        .to_owned()
+                        + "\n",
+                );
+        }
+    }
+
+    mod format {
+        use super::*;
+
+        #[test]
+        fn components() {
+            pathmut(&["format", "{parent}/{stem}_thumb.webp", "a/b.png"])
+                .success()
+                .stdout("a/b_thumb.webp\n");
+            pathmut(&["format", "out/{name}", "a/b.png", "/c/d.txt"])
+                .success()
+                .stdout("out/b.png\nout/d.txt\n");
+            pathmut(&["format", "{prefix}.{ext}", "/my/file.tar.gz"])
+                .success()
+                .stdout("file.gz\n");
+            pathmut(&["format", "{disk}-{name}", "C:\\path\\file.txt"])
+                .success()
+                .stdout("C-file.txt\n");
+        }
+
+        #[test]
+        fn nth() {
+            pathmut(&["format", "{1}/{-1}", "/my/path/file.txt"])
+                .success()
+                .stdout("my/file.txt\n");
+        }
+
+        #[test]
+        fn default() {
+            pathmut(&[
+                "format",
+                "{parent|.}/{stem}.{ext|txt}",
+                "file",
+                "dir/file.md",
+            ])
+            .success()
+            .stdout("./file.txt\ndir/file.md\n");
+        }
+
+        #[test]
+        fn braces() {
+            pathmut(&["format", "{{{stem}}}", "file.txt"])
+                .success()
+                .stdout("{file}\n");
+        }
+
+        #[test]
+        fn invalid() {
+            pathmut(&["format", "{nope}", "file.txt"]).failure();
+            pathmut(&["format", "{stem", "file.txt"]).failure();
+            pathmut(&["format", "stem}", "file.txt"]).failure();
+        }
+    }
+
+    #[test]
+    fn from_stdin() {
+        Command::cargo_bin("pathmut")
+            .unwrap()
+            .args(["get", "ext"])
+            .write_stdin("/my/path/file.txt")
+            .assert()
+            .success()
+            .stdout("txt\n");
+    }
+
+    #[test]
+    fn help_default() {
+        pathmut(&[])
+            .failure()
+            .stderr(predicate::str::contains("Print help information"));
+    }
+
+    #[test]
+    fn multiple_paths() {
+        pathmut(&["get", "ext", "file.txt", "another.png"])
+            .success()
+            .stdout("txt\npng\n");
+        pathmut(&["ext", "file.txt", "another.png"])
+            .success()
+            .stdout("txt\npng\n");
+        pathmut(&["get", "stem", "file.txt", "another.png"])
+            .success()
+            .stdout("file\nanother\n");
+        pathmut(&["stem", "file.txt", "another.png"])
+            .success()
+            .stdout("file\nanother\n");
+        pathmut(&[
+            "set",
+            "blah",
+            "stem",
+            "path/to/file.txt",
+            "just/another.png",
+        ])
+        .success()
+        .stdout("path/to/blah.txt\njust/blah.png\n");
+    }
+
+    #[test]
+    fn windows() {
+        pathmut(&["get", "ext", r"C:\Users\username\file.txt"])
+            .success()
+            .stdout("txt\n");
+        pathmut(&["get", "stem", r"C:\Users\username\file.txt"])
+            .success()
+            .stdout("file\n");
+        pathmut(&["get", "name", r"C:\Users\username\file.txt"])
+            .success()
+            .stdout("file.txt\n");
+    }
+
+    #[test]
+    fn piped_multiple_paths() {
+        Command::cargo_bin("pathmut")
+            .unwrap()
+            .args(["ext"])
+            .write_stdin("/my/path/file.txt\npicture.png")
+            .assert()
+            .success()
+            .stdout("txt\npng\n");
+    }
+
+    mod json {
+        use super::*;
+
+        #[test]
+        fn get() {
+            pathmut(&["--output", "json", "get", "ext", "file.txt", "dir"])
+                .success()
+                .stdout(
+                    r#"[{"input":"file.txt","output":"txt"},{"input":"dir","output":""}]"#
+                        .to_owned()
+                        + "\n",
+                );
+            pathmut(&["--output", "jsonl", "set", "md", "ext", "file.txt"])
+                .success()
+                .stdout("{\"input\":\"file.txt\",\"output\":\"file.md\"}\n");
+        }
+
+        #[test]
+        fn default_command() {
+            pathmut(&["stem", "--output", "jsonl", "file.txt"])
+                .success()
+                .stdout("{\"input\":\"file.txt\",\"output\":\"file\"}\n");
+        }
+
+        #[test]
+        fn empty() {
+            pathmut(&["--output", "json", "get", "ext"])
+                .success()
+                .stdout("[]\n");
+        }
+
+        #[test]
+        fn escapes() {
+            pathmut(&["--output", "jsonl", "convert", "win", "my/\"path\"/file"])
+                .success()
+                .stdout(
+                    r#"{"input":"my/\"path\"/file","output":"my\\\"path\"\\file"}"#.to_owned()
+                        + "\n",
+                );
+        }
+
+        #[test]
+        fn is() {
+            pathmut(&["--output", "jsonl", "is", "absolute", "--all", "/a", "b"])
+                .failure()
+                .stdout(
+                    "{\"input\":\"/a\",\"output\":true}\n\
+                    {\"input\":\"b\",\"output\":false}\n\
+                    {\"all\":false}\n",
+                );
+            pathmut(&["--output", "json", "has", "-p", "ext", "a", "b.c"])
+                .success()
+                .stdout(
+                    r#"[{"input":"a","output":false},{"input":"b.c","output":true},{"any":true}]"#
+                        .to_owned()
+                        + "\n",
+                );
+        }
+
+        #[test]
+        fn errors() {
+            pathmut(&["--output", "json", "get", "5", "a/b", "a/b/c/d/e/f"])
+                .code(3)
+                .stdout(
+                    r#"[{"input":"a/b","error":"component 5 is out of range for 'a/b'"},{"input":"a/b/c/d/e/f","output":"f"}]"#
+                        .to_owned()
+                        + "\n",
+                );
+            pathmut(&["--output", "jsonl", "--as-git", "get", "repo", "a/b"])
+                .failure()
+                .stdout("{\"input\":\"a/b\",\"error\":\"not a git remote 'a/b'\"}\n");
+            pathmut(&["--output", "jsonl", "join", "--strict", "a", "/b"])
+                .failure()
+                .stdout("{\"input\":\"/b\",\"error\":\"cannot join absolute path '/b'\"}\n");
+            pathmut(&["--output", "jsonl", "common", "/a", "b"])
+                .failure()
+                .stdout("{\"input\":[\"/a\",\"b\"],\"error\":\"paths have no common ancestor\"}\n");
+        }
+
+        #[test]
+        fn depth() {
+            pathmut(&["--output", "jsonl", "depth", "/my/path"])
+                .success()
+                .stdout("{\"input\":\"/my/path\",\"output\":2}\n");
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn non_utf8() {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(["--output", "jsonl", "ext"])
+                .arg(OsStr::from_bytes(b"\xff.txt"))
+                .assert()
+                .success()
+                .stdout("{\"input\":{\"bytes\":[255,46,116,120,116]},\"output\":\"txt\"}\n");
+        }
+    }
+
+    #[cfg(unix)]
+    mod non_utf8 {
+        use super::*;
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        fn pathmut_bytes(args: &[&[u8]]) -> Assert {
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(args.iter().map(|arg| OsStr::from_bytes(arg)))
+                .assert()
+        }
+
+        #[test]
+        fn get() {
+            pathmut_bytes(&[b"get", b"stem", b"/my/caf\xe9.txt"])
+                .success()
+                .stdout(b"caf\xe9\n".to_vec());
+        }
+
+        #[test]
+        fn set() {
+            pathmut_bytes(&[b"set", b"md", b"ext", b"/my/caf\xe9.txt"])
+                .success()
+                .stdout(b"/my/caf\xe9.md\n".to_vec());
+            pathmut_bytes(&[b"set", b"\xff", b"ext", b"file.txt"])
+                .success()
+                .stdout(b"file.\xff\n".to_vec());
+        }
+
+        #[test]
+        fn normalize() {
+            pathmut_bytes(&[b"normalize", b"\xe9/./\xff/.."])
+                .success()
+                .stdout(b"\xe9\n".to_vec());
+        }
+
+        #[test]
+        fn piped() {
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(["get", "name"])
+                .write_stdin(b"/my/caf\xe9.txt\n/\xff\xfe\n".to_vec())
+                .assert()
+                .success()
+                .stdout(b"caf\xe9.txt\n\xff\xfe\n".to_vec());
+        }
+    }
+
+    mod piped {
+        use super::*;
+
+        fn piped(args: &[&str], stdin: &str) -> Assert {
+            Command::cargo_bin("pathmut")
+                .unwrap()
+                .args(args)
+                .write_stdin(stdin)
+                .assert()
+        }
+
+        #[test]
+        fn get() {
+            piped(&["get", "name"], "/my/path/file.txt\nanother.png\n")
+                .success()
+                .stdout("file.txt\nanother.png\n");
+        }
+
+        #[test]
+        fn set() {
+            piped(&["set", "md", "ext"], "file.txt\nimage.png\n")
+                .success()
+                .stdout("file.md\nimage.md\n");
+        }
+
+        #[test]
+        fn is() {
+            piped(&["is", "absolute", "--all"], "/my/path\nmy/path\n").failure();
+            piped(&["is", "absolute", "--any", "-p"], "/my/path\nmy/path\n")
+                .success()
+                .stdout("true\n");
+        }
+
+        #[test]
+        fn has() {
+            piped(&["has", "ext", "--all", "-p"], "file.txt\nfile\n")
+                .success()
+                .stdout("false\n");
+        }
+
+        #[test]
+        fn depth() {
+            piped(&["depth"], "/\n/my/path\n")
+                .success()
+                .stdout("0\n2\n");
+        }
+
+        #[test]
+        fn convert() {
+            piped(&["convert", "win"], "my/path\n/my/path\n")
+                .success()
+                .stdout("my\\path\n\\my\\path\n");
+        }
+
+        #[test]
+        fn normalize() {
+            piped(&["normalize"], "my/./path\nmy/path/..\n")
+                .success()
+                .stdout("my/path\nmy\n");
+        }
+
+        #[test]
+        fn arguments_take_precedence() {
+            piped(&["get", "ext", "file.md"], "file.txt\n")
+                .success()
+                .stdout("md\n");
+        }
+
+        #[test]
+        fn empty() {
+            piped(&["get", "ext"], "").success().stdout("");
+        }
     }
-    */
 }
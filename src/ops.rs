@@ -0,0 +1,425 @@
+use crate::command::{PathKind, Question};
+use crate::component::{path_type, Component};
+use crate::convert::convert;
+use crate::error::PathmutError;
+use crate::git::GitRemote;
+use crate::relation::{contains, ends_with, rebase, relative_to, starts_with, strip_prefix};
+use crate::template::Template;
+use crate::uri::Uri;
+use crate::valid::{is_valid, Platform, Sanitizer};
+use crate::Action;
+use typed_path::{TypedComponent, TypedPath, TypedPathBuf, WindowsComponent, WindowsPrefix};
+
+/// Change pathmut makes to a path, so the same change can be kept and
+/// applied to any number of paths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathMut {
+    Get(Component),
+    Set(Component, Vec<u8>),
+    /// Set the component only if the path has it
+    Replace(Component, Vec<u8>),
+    Delete(Component),
+    Normalize,
+    Convert {
+        to: PathKind,
+        from: Option<PathKind>,
+    },
+    Format(Template),
+    /// Join the path onto the base.  When strict, absolute paths aren't
+    /// joined, since they would replace the base.
+    Join {
+        base: TypedPathBuf,
+        strict: bool,
+    },
+    /// Path leading from the base directory to the path
+    Relative(TypedPathBuf),
+    StripPrefix(TypedPathBuf),
+    Rebase {
+        old: TypedPathBuf,
+        new: TypedPathBuf,
+    },
+    /// Make the component a safe file name, leaving paths without it alone
+    Sanitize(Component, Sanitizer),
+    /// Resolve a URI reference against the base, only for URIs
+    Resolve(Uri),
+}
+
+impl PathMut {
+    /// The changed path, or a component for [`PathMut::Get`].  Fails when
+    /// the change doesn't make sense for the path, like the disk of a unix
+    /// path, a relative path from a base of another type or a prefix the
+    /// path doesn't start with.
+    pub fn apply(&self, path: &TypedPath) -> Result<Vec<u8>, PathmutError> {
+        let missing = || PathmutError::NotApplicable;
+        let changed = match self {
+            PathMut::Get(component) => component.try_get(path)?,
+            PathMut::Set(component, value) => component.try_set(path, value)?,
+            PathMut::Replace(component, value) => component.try_replace(path, value)?,
+            PathMut::Delete(component) => component.try_delete(path)?,
+            PathMut::Normalize => path.normalize().into_vec(),
+            PathMut::Convert { to, from } => {
+                convert(path, *to, *from).ok_or_else(missing)?.into_vec()
+            }
+            PathMut::Format(template) => template.render(path),
+            PathMut::Join { strict: true, .. } if path.is_absolute() => return Err(missing()),
+            PathMut::Join { base, .. } => base.join(path.as_bytes()).into_vec(),
+            PathMut::Relative(base) => relative_to(path, &base.to_path())
+                .ok_or_else(missing)?
+                .into_vec(),
+            PathMut::StripPrefix(prefix) => strip_prefix(path, &prefix.to_path())
+                .ok_or_else(missing)?
+                .into_vec(),
+            PathMut::Rebase { old, new } => rebase(path, &old.to_path(), &new.to_path())
+                .ok_or_else(missing)?
+                .into_vec(),
+            PathMut::Sanitize(component, sanitizer) => {
+                let value = component.get(path);
+                if value.is_empty() {
+                    path.as_bytes().to_vec()
+                } else {
+                    component.set(path, &sanitizer.sanitize(&value))
+                }
+            }
+            PathMut::Resolve(_) => return Err(missing()),
+        };
+        Ok(changed)
+    }
+
+    /// The changed URI, where components are changed like
+    /// [`Component::try_action_uri`].  Only getting, setting, replacing and
+    /// deleting components, normalizing and resolving apply to URIs.
+    pub fn apply_uri(&self, uri: &Uri) -> Result<Vec<u8>, PathmutError> {
+        match self {
+            PathMut::Get(component) => component.try_action_uri(&Action::Get, uri),
+            PathMut::Set(component, value) => component.try_action_uri(&Action::Set(value), uri),
+            PathMut::Replace(component, value) => {
+                component.try_action_uri(&Action::Replace(value), uri)
+            }
+            PathMut::Delete(component) => component.try_action_uri(&Action::Delete, uri),
+            PathMut::Normalize => Ok(uri.normalize().to_bytes()),
+            PathMut::Resolve(base) => Ok(base.resolve(uri).to_bytes()),
+            _ => Err(PathmutError::NotApplicable),
+        }
+    }
+
+    /// The changed git remote, where components are changed like
+    /// [`Component::try_action_git`].  Only getting, setting, replacing and
+    /// deleting components and converting to another form of remote apply
+    /// to remotes.
+    pub fn apply_git(&self, remote: &GitRemote) -> Result<Vec<u8>, PathmutError> {
+        match self {
+            PathMut::Get(component) => component.try_action_git(&Action::Get, remote),
+            PathMut::Set(component, value) => component.try_action_git(&Action::Set(value), remote),
+            PathMut::Replace(component, value) => {
+                component.try_action_git(&Action::Replace(value), remote)
+            }
+            PathMut::Delete(component) => component.try_action_git(&Action::Delete, remote),
+            PathMut::Convert { to, .. } => match to.remote_form() {
+                Some(form) => Ok(remote.convert(form).to_bytes()),
+                None => Err(PathmutError::NotApplicable),
+            },
+            _ => Err(PathmutError::NotApplicable),
+        }
+    }
+}
+
+impl From<(Component, Action<'_>)> for PathMut {
+    fn from((component, action): (Component, Action)) -> Self {
+        match action {
+            Action::Get => PathMut::Get(component),
+            Action::Set(value) => PathMut::Set(component, value.to_vec()),
+            Action::Replace(value) => PathMut::Replace(component, value.to_vec()),
+            Action::Delete => PathMut::Delete(component),
+        }
+    }
+}
+
+/// Join every path onto the base in order.  When strict, the first
+/// absolute path is returned as the error rather than replacing
+/// everything joined before it.
+pub fn join_all<'a>(
+    base: &TypedPath,
+    paths: impl IntoIterator<Item = TypedPath<'a>>,
+    strict: bool,
+) -> Result<TypedPathBuf, TypedPath<'a>> {
+    let mut joined = base.to_path_buf();
+    for path in paths {
+        if strict && path.is_absolute() {
+            return Err(path);
+        }
+        joined = joined.join(path.as_bytes());
+    }
+    Ok(joined)
+}
+
+/// Number of components before the last, where a windows prefix like
+/// `C:` doesn't count
+pub fn depth(path: &TypedPath) -> usize {
+    path.components()
+        .filter(|c| !matches!(c, TypedComponent::Windows(WindowsComponent::Prefix(_))))
+        .count()
+        .saturating_sub(1)
+}
+
+/// Whether normalizing the path leaves it as it is.  Comparing
+/// components isn't enough, since they skip `.` and repeated separators.
+pub fn is_normalized(path: &TypedPath) -> bool {
+    path.normalize().as_bytes() == path.as_bytes()
+}
+
+impl Question {
+    /// Answer the question about the path.  Questions that compare paths
+    /// compare against the reference and fail without one, and `valid`
+    /// checks the rules of the platform, or of the path's own type without
+    /// one.
+    pub fn ask(
+        self,
+        path: &TypedPath,
+        reference: Option<&TypedPath>,
+        platform: Option<Platform>,
+    ) -> Result<bool, PathmutError> {
+        let compare = |relation: fn(&TypedPath, &TypedPath) -> bool| {
+            reference
+                .map(|reference| relation(path, reference))
+                .ok_or(PathmutError::NotApplicable)
+        };
+        let answer = match self {
+            Question::Absolute => path.is_absolute(),
+            Question::Relative => path.is_relative(),
+            Question::Unix => path.is_unix(),
+            Question::Windows => path.is_windows(),
+            Question::Normalized => is_normalized(path),
+            Question::StartsWith => return compare(starts_with),
+            Question::EndsWith => return compare(ends_with),
+            Question::Contains => return compare(contains),
+            Question::Valid => is_valid(path, platform.unwrap_or(path_type(path).into())),
+        };
+        Ok(answer)
+    }
+}
+
+/// Components [`Info`] lists, with their names
+pub const INFO_COMPONENTS: [(Component, &str); 6] = [
+    (Component::Disk, "disk"),
+    (Component::Parent, "parent"),
+    (Component::Name, "name"),
+    (Component::Prefix, "prefix"),
+    (Component::Stem, "stem"),
+    (Component::Extension, "extension"),
+];
+
+/// Everything pathmut can tell about a path at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    pub unix: bool,
+    /// Kind of windows prefix, if the path has one
+    pub windows_prefix: Option<&'static str>,
+    pub absolute: bool,
+    pub normalized: bool,
+    /// Value of each of [`INFO_COMPONENTS`]
+    pub values: Vec<(&'static str, Vec<u8>)>,
+    pub components: Vec<Vec<u8>>,
+}
+
+impl Info {
+    pub fn of(path: &TypedPath) -> Self {
+        Info {
+            unix: path.is_unix(),
+            windows_prefix: windows_prefix(path),
+            absolute: path.is_absolute(),
+            normalized: is_normalized(path),
+            values: INFO_COMPONENTS
+                .iter()
+                .map(|(component, name)| (*name, component.get(path)))
+                .collect(),
+            components: path.components().map(|c| c.as_bytes().to_vec()).collect(),
+        }
+    }
+}
+
+/// Kind of windows prefix, if the path has one
+pub fn windows_prefix(path: &TypedPath) -> Option<&'static str> {
+    let TypedPath::Windows(path) = path else {
+        return None;
+    };
+    let Some(WindowsComponent::Prefix(prefix)) = path.components().next() else {
+        return None;
+    };
+    let kind = match prefix.kind() {
+        WindowsPrefix::Disk(_) => "disk",
+        WindowsPrefix::UNC(..) => "unc",
+        WindowsPrefix::Verbatim(_) => "verbatim",
+        WindowsPrefix::VerbatimUNC(..) => "verbatim unc",
+        WindowsPrefix::VerbatimDisk(_) => "verbatim disk",
+        WindowsPrefix::DeviceNS(_) => "device",
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn apply(path_mut: PathMut, path: &str) -> Result<Vec<u8>, PathmutError> {
+        path_mut.apply(&TypedPath::derive(path))
+    }
+
+    #[test]
+    fn apply_components() {
+        let ext = PathMut::from((Component::Extension, Action::Set(b"md")));
+        assert_eq!(apply(ext, "notes.txt"), Ok(b"notes.md".to_vec()));
+        assert_eq!(
+            apply(PathMut::Get(Component::Nth(3)), "a/b"),
+            Err(PathmutError::OutOfRange(Component::Nth(3)))
+        );
+        assert_eq!(
+            apply(PathMut::Set(Component::Disk, b"C".to_vec()), "/a"),
+            Err(PathmutError::NotApplicable)
+        );
+        assert_eq!(
+            apply(PathMut::Replace(Component::Disk, b"C".to_vec()), "/a"),
+            Ok(b"/a".to_vec())
+        );
+    }
+
+    #[test]
+    fn apply_paths() {
+        let base = TypedPathBuf::from("/a");
+        assert_eq!(apply(PathMut::Normalize, "a/./b//c"), Ok(b"a/b/c".to_vec()));
+        assert_eq!(
+            apply(PathMut::Relative(base.clone()), "/a/b/c"),
+            Ok(b"b/c".to_vec())
+        );
+        assert_eq!(
+            apply(PathMut::StripPrefix(base.clone()), "/b"),
+            Err(PathmutError::NotApplicable)
+        );
+        assert_eq!(
+            apply(
+                PathMut::Convert {
+                    to: PathKind::Windows,
+                    from: None
+                },
+                "a/b"
+            ),
+            Ok(br"a\b".to_vec())
+        );
+        assert_eq!(
+            apply(PathMut::Resolve(Uri::parse(b"https://x/")), "a"),
+            Err(PathmutError::NotApplicable)
+        );
+    }
+
+    #[test]
+    fn apply_join() {
+        let join = |strict| PathMut::Join {
+            base: TypedPathBuf::from("/a"),
+            strict,
+        };
+        assert_eq!(apply(join(false), "b"), Ok(b"/a/b".to_vec()));
+        assert_eq!(apply(join(false), "/b"), Ok(b"/b".to_vec()));
+        assert_eq!(apply(join(true), "/b"), Err(PathmutError::NotApplicable));
+    }
+
+    #[test]
+    fn apply_uri_and_git() {
+        let uri = Uri::parse(b"https://x/a/b.txt");
+        let stem = PathMut::Set(Component::Stem, b"c".to_vec());
+        assert_eq!(stem.apply_uri(&uri), Ok(b"https://x/a/c.txt".to_vec()));
+        assert_eq!(
+            PathMut::Get(Component::Disk).apply_uri(&uri),
+            Err(PathmutError::NotApplicable)
+        );
+        assert_eq!(
+            PathMut::Resolve(Uri::parse(b"https://x/a/")).apply_uri(&Uri::parse(b"../b")),
+            Ok(b"https://x/b".to_vec())
+        );
+        assert_eq!(
+            PathMut::Sanitize(Component::Name, Sanitizer::new(Platform::Unix)).apply_uri(&uri),
+            Err(PathmutError::NotApplicable)
+        );
+
+        let remote = GitRemote::parse(b"git@h:o/r.git").unwrap();
+        assert_eq!(
+            PathMut::Get(Component::Repo).apply_git(&remote),
+            Ok(b"r".to_vec())
+        );
+        assert_eq!(
+            PathMut::Get(Component::Query).apply_git(&remote),
+            Err(PathmutError::NotApplicable)
+        );
+        let https = PathMut::Convert {
+            to: PathKind::Https,
+            from: None,
+        };
+        assert_eq!(https.apply_git(&remote), Ok(b"https://h/o/r".to_vec()));
+        assert_eq!(
+            PathMut::Normalize.apply_git(&remote),
+            Err(PathmutError::NotApplicable)
+        );
+    }
+
+    #[test]
+    fn join_every_path() {
+        let base = TypedPath::derive("/a");
+        let paths = || ["b", "/c", "d"].map(TypedPath::derive);
+        assert_eq!(
+            join_all(&base, paths(), false),
+            Ok(TypedPathBuf::from("/c/d"))
+        );
+        assert_eq!(join_all(&base, paths(), true), Err(TypedPath::derive("/c")));
+        assert_eq!(join_all(&base, [], true), Ok(TypedPathBuf::from("/a")));
+    }
+
+    #[test]
+    fn depth_and_normalized() {
+        assert_eq!(depth(&TypedPath::derive("/a/b")), 2);
+        assert_eq!(depth(&TypedPath::derive(r"C:\a")), 1);
+        assert_eq!(depth(&TypedPath::derive("")), 0);
+        assert!(is_normalized(&TypedPath::derive("a/b")));
+        assert!(!is_normalized(&TypedPath::derive("a/./b")));
+        assert!(!is_normalized(&TypedPath::derive("a//b")));
+    }
+
+    #[test]
+    fn ask() {
+        let path = TypedPath::derive("/a/b/c");
+        let ask = |question: Question, reference| {
+            question.ask(&path, Some(&TypedPath::derive(reference)), None)
+        };
+        assert_eq!(Question::Absolute.ask(&path, None, None), Ok(true));
+        assert_eq!(Question::Relative.ask(&path, None, None), Ok(false));
+        assert_eq!(ask(Question::StartsWith, "/a"), Ok(true));
+        assert_eq!(ask(Question::EndsWith, "b/c"), Ok(true));
+        assert_eq!(ask(Question::Contains, "b"), Ok(true));
+        assert_eq!(ask(Question::StartsWith, "/b"), Ok(false));
+        assert_eq!(
+            Question::Contains.ask(&path, None, None),
+            Err(PathmutError::NotApplicable)
+        );
+
+        let question = TypedPath::derive("a?b");
+        assert_eq!(Question::Valid.ask(&question, None, None), Ok(true));
+        assert_eq!(
+            Question::Valid.ask(&question, None, Some(Platform::Windows)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn info() {
+        let info = Info::of(&TypedPath::derive(r"C:\dir\file.tar.gz"));
+        assert!(!info.unix);
+        assert_eq!(info.windows_prefix, Some("disk"));
+        assert!(info.absolute);
+        assert!(info.normalized);
+        assert_eq!(info.values[0], ("disk", b"C".to_vec()));
+        assert_eq!(info.values[4], ("stem", b"file.tar".to_vec()));
+        assert_eq!(info.components.len(), 4);
+
+        let info = Info::of(&TypedPath::derive("a/../b"));
+        assert!(info.unix);
+        assert_eq!(info.windows_prefix, None);
+        assert!(!info.normalized);
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{self, Write};
+
+use pathmut::OutputFormat;
+
+/// A JSON value borrowing its strings from the results being printed
+pub enum Json<'a> {
+    Null,
+    Bool(bool),
+    Number(isize),
+    /// Written as a string when valid UTF-8, otherwise as `{"bytes": [...]}`
+    /// so that no byte is lost
+    Bytes(&'a [u8]),
+    Str(&'a str),
+    List(Vec<Json<'a>>),
+    Object(Vec<(&'a str, Json<'a>)>),
+}
+
+impl Json<'_> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Json::Null => buf.extend_from_slice(b"null"),
+            Json::Bool(b) => buf.extend_from_slice(if *b { b"true" } else { b"false" }),
+            Json::Number(n) => buf.extend_from_slice(n.to_string().as_bytes()),
+            Json::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(s) => encode_str(s, buf),
+                Err(_) => {
+                    let bytes = bytes.iter().map(|b| Json::Number(*b as isize)).collect();
+                    Json::Object(vec![("bytes", Json::List(bytes))]).encode(buf)
+                }
+            },
+            Json::Str(s) => encode_str(s, buf),
+            Json::List(values) => {
+                buf.push(b'[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        buf.push(b',');
+                    }
+                    value.encode(buf);
+                }
+                buf.push(b']');
+            }
+            Json::Object(fields) => {
+                buf.push(b'{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        buf.push(b',');
+                    }
+                    encode_str(key, buf);
+                    buf.push(b':');
+                    value.encode(buf);
+                }
+                buf.push(b'}');
+            }
+        }
+    }
+}
+
+fn encode_str(s: &str, buf: &mut Vec<u8>) {
+    buf.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.extend_from_slice(b"\\\""),
+            '\\' => buf.extend_from_slice(b"\\\\"),
+            '\n' => buf.extend_from_slice(b"\\n"),
+            '\r' => buf.extend_from_slice(b"\\r"),
+            '\t' => buf.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => {
+                buf.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    buf.push(b'"');
+}
+
+/// Writes results to stdout in the chosen format, each followed by
+/// the record separator
+pub struct Output {
+    out: io::StdoutLock<'static>,
+    separator: u8,
+    format: OutputFormat,
+    records: usize,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat, separator: u8) -> Self {
+        Output {
+            out: io::stdout().lock(),
+            separator,
+            format,
+            records: 0,
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    /// Write a plain text record
+    pub fn write(&mut self, record: &[u8]) {
+        self.emit(record, true);
+    }
+
+    /// Write a json record, which is ignored in text mode
+    pub fn record(&mut self, value: Json) {
+        let mut buf = Vec::new();
+        match self.format {
+            OutputFormat::Text => return,
+            OutputFormat::Json => {
+                buf.push(if self.records == 0 { b'[' } else { b',' });
+                value.encode(&mut buf);
+                self.emit(&buf, false);
+            }
+            OutputFormat::JsonLines => {
+                value.encode(&mut buf);
+                self.emit(&buf, true);
+            }
+        }
+        self.records += 1;
+    }
+
+    /// Result of transforming the path `input`
+    pub fn path(&mut self, input: &[u8], output: &[u8]) {
+        if self.is_text() {
+            self.write(output);
+        } else {
+            self.record(Json::Object(vec![
+                ("input", Json::Bytes(input)),
+                ("output", Json::Bytes(output)),
+            ]));
+        }
+    }
+
+    /// Result of combining all the paths in `inputs`
+    pub fn paths<'a>(&mut self, inputs: impl Iterator<Item = &'a [u8]>, output: &[u8]) {
+        if self.is_text() {
+            self.write(output);
+        } else {
+            self.record(Json::Object(vec![
+                ("input", Json::List(inputs.map(Json::Bytes).collect())),
+                ("output", Json::Bytes(output)),
+            ]));
+        }
+    }
+
+    /// Reason the path or paths in `input` couldn't be handled, only
+    /// written as json since text goes to stderr
+    pub fn error(&mut self, input: Json, error: &str) {
+        self.record(Json::Object(vec![
+            ("input", input),
+            ("error", Json::Str(error)),
+        ]));
+    }
+
+    /// Numeric result of measuring the path `input`
+    pub fn number(&mut self, input: &[u8], output: isize) {
+        if self.is_text() {
+            self.write(output.to_string().as_bytes());
+        } else {
+            self.record(Json::Object(vec![
+                ("input", Json::Bytes(input)),
+                ("output", Json::Number(output)),
+            ]));
+        }
+    }
+
+    /// Answer to a question for a single path, only written as json
+    pub fn answer(&mut self, input: &[u8], answer: bool) {
+        self.record(Json::Object(vec![
+            ("input", Json::Bytes(input)),
+            ("output", Json::Bool(answer)),
+        ]));
+    }
+
+    /// Answer to a question for a single path along with the reasons it is
+    /// false, which are written as text only when there are some
+    pub fn explained(&mut self, input: &[u8], answer: bool, reasons: &[String]) {
+        if self.is_text() {
+            if !reasons.is_empty() {
+                let mut line = input.to_vec();
+                line.extend_from_slice(b": ");
+                line.extend_from_slice(reasons.join(", ").as_bytes());
+                self.write(&line);
+            }
+        } else {
+            let reasons = reasons.iter().map(|reason| Json::Str(reason)).collect();
+            self.record(Json::Object(vec![
+                ("input", Json::Bytes(input)),
+                ("output", Json::Bool(answer)),
+                ("reasons", Json::List(reasons)),
+            ]));
+        }
+    }
+
+    /// Answer to a question over all paths, named `any` or `all`.  Text
+    /// is only written when `print` is set.
+    pub fn aggregate(&mut self, name: &str, answer: bool, print: bool) {
+        if !self.is_text() {
+            self.record(Json::Object(vec![(name, Json::Bool(answer))]));
+        } else if print {
+            self.write(if answer { b"true" } else { b"false" });
+        }
+    }
+
+    fn emit(&mut self, bytes: &[u8], separate: bool) {
+        self.out
+            .write_all(bytes)
+            .and_then(|_| {
+                if separate {
+                    self.out.write_all(&[self.separator])
+                } else {
+                    Ok(())
+                }
+            })
+            .and_then(|_| self.out.flush())
+            .expect("failed printing to stdout");
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        if self.format == OutputFormat::Json {
+            let close: &[u8] = if self.records == 0 { b"[]" } else { b"]" };
+            let _ = self
+                .out
+                .write_all(close)
+                .and_then(|_| self.out.write_all(&[self.separator]));
+        }
+    }
+}
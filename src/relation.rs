@@ -0,0 +1,206 @@
+use crate::component::{join_components, path_type};
+use typed_path::{PathType, TypedComponent, TypedPath, TypedPathBuf};
+
+fn components<'a>(path: &'a TypedPath) -> Vec<TypedComponent<'a>> {
+    path.components().collect()
+}
+
+/// Whether the components match one to one, where prefixes like windows
+/// disks ignore case
+fn same_components(a: &[TypedComponent], b: &[TypedComponent]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_component(a, b))
+}
+
+/// Whether the components of `path` begin with every component of `base`.
+/// Paths of different types are never related.
+pub fn starts_with(path: &TypedPath, base: &TypedPath) -> bool {
+    if path.is_unix() != base.is_unix() {
+        return false;
+    }
+    let (path, base) = (components(path), components(base));
+    path.len() >= base.len() && same_components(&path[..base.len()], &base)
+}
+
+/// Whether the components of `path` end with every component of `child`
+pub fn ends_with(path: &TypedPath, child: &TypedPath) -> bool {
+    if path.is_unix() != child.is_unix() {
+        return false;
+    }
+    let (path, child) = (components(path), components(child));
+    path.len() >= child.len() && same_components(&path[path.len() - child.len()..], &child)
+}
+
+/// Whether the components of `part` appear consecutively within `path`
+pub fn contains(path: &TypedPath, part: &TypedPath) -> bool {
+    if path.is_unix() != part.is_unix() {
+        return false;
+    }
+    let part = components(part);
+    part.is_empty()
+        || components(path)
+            .windows(part.len())
+            .any(|w| same_components(w, &part))
+}
+
+/// Components with `.` removed and `..` resolved against the component
+/// before it, keeping a leading `..` that can't be resolved.  Also returns
+/// the number of prefix and root components at the start.
+fn resolved_components<'a>(path: &'a TypedPath) -> (Vec<&'a [u8]>, usize) {
+    let mut resolved: Vec<&[u8]> = Vec::new();
+    let mut anchors = 0;
+    let mut rooted = false;
+    for c in path.components() {
+        if c.is_current() {
+            continue;
+        } else if c.is_parent() {
+            match resolved.last() {
+                Some(last) if resolved.len() > anchors && *last != b".." => {
+                    resolved.pop();
+                }
+                _ if rooted => {}
+                _ => resolved.push(c.as_bytes()),
+            }
+        } else {
+            if !c.is_normal() {
+                rooted |= c.is_root();
+                anchors += 1;
+            }
+            resolved.push(c.as_bytes());
+        }
+    }
+    (resolved, anchors)
+}
+
+/// Path leading from the directory `base` to `path`, worked out only from
+/// the path strings.  Returns `None` when there is no such path, like
+/// between different disks or from a relative base to an absolute path.
+pub fn relative_to(path: &TypedPath, base: &TypedPath) -> Option<TypedPathBuf> {
+    if path.is_unix() != base.is_unix() {
+        return None;
+    }
+    let (path_components, anchors) = resolved_components(path);
+    let (base_components, base_anchors) = resolved_components(base);
+
+    // disks are the same regardless of case
+    let same_anchors = anchors == base_anchors
+        && path_components[..anchors]
+            .iter()
+            .zip(&base_components[..anchors])
+            .all(|(a, b)| a.eq_ignore_ascii_case(b));
+    if !same_anchors {
+        return None;
+    }
+
+    let common = anchors
+        + path_components[anchors..]
+            .iter()
+            .zip(&base_components[anchors..])
+            .take_while(|(a, b)| a == b)
+            .count();
+
+    // can't climb back out of a directory that is only known by ..
+    let climb = &base_components[common..];
+    if climb.contains(&&b".."[..]) {
+        return None;
+    }
+
+    let components = climb
+        .iter()
+        .map(|_| &b".."[..])
+        .chain(path_components[common..].iter().copied());
+    let relative = join_components(components, path_type(path));
+    if relative.as_bytes().is_empty() {
+        Some(join_components([&b"."[..]], path_type(path)))
+    } else {
+        Some(relative)
+    }
+}
+
+/// Prefix and root components are anchors, which compare disks
+/// regardless of case
+fn is_anchor(c: &TypedComponent) -> bool {
+    !c.is_normal() && !c.is_parent() && !c.is_current()
+}
+
+fn same_component(a: &TypedComponent, b: &TypedComponent) -> bool {
+    if is_anchor(a) {
+        a.as_bytes().eq_ignore_ascii_case(b.as_bytes())
+    } else {
+        a.as_bytes() == b.as_bytes()
+    }
+}
+
+/// Deepest path that contains every path, compared component by component.
+/// Returns `None` when the paths are anchored differently, like absolute
+/// and relative paths or paths on different disks, or when there are none.
+/// A single path, like a file, is its own common ancestor.
+pub fn common_ancestor<'a>(paths: impl IntoIterator<Item = TypedPath<'a>>) -> Option<TypedPathBuf> {
+    let mut paths = paths.into_iter();
+    let first = paths.next()?;
+    let anchors = first.components().take_while(is_anchor).count();
+    let mut common: Vec<TypedComponent> = first.components().collect();
+
+    for path in paths {
+        if path.is_unix() != first.is_unix()
+            || path.components().take_while(is_anchor).count() != anchors
+        {
+            return None;
+        }
+        let shared = common
+            .iter()
+            .zip(path.components())
+            .take_while(|(a, b)| same_component(a, b))
+            .count();
+        if shared < anchors {
+            return None;
+        }
+        common.truncate(shared);
+    }
+
+    if common.is_empty() {
+        return Some(join_components([&b"."[..]], path_type(&first)));
+    }
+    Some(join_components(
+        common.iter().map(|c| c.as_bytes()),
+        path_type(&first),
+    ))
+}
+
+/// Components of `path` after every component of `prefix`, or `None` when
+/// `path` isn't under `prefix`
+fn remainder<'a>(path: &'a TypedPath, prefix: &TypedPath) -> Option<Vec<&'a [u8]>> {
+    if path.is_unix() != prefix.is_unix() {
+        return None;
+    }
+    let mut components = path.components();
+    for p in prefix.components() {
+        match components.next() {
+            Some(c) if same_component(&p, &c) => {}
+            _ => return None,
+        }
+    }
+    Some(components.map(|c| c.as_bytes()).collect())
+}
+
+/// Path left after removing the leading components of `prefix`, which is
+/// `.` when the path is the prefix itself.  Returns `None` when `path`
+/// isn't under `prefix`.
+pub fn strip_prefix(path: &TypedPath, prefix: &TypedPath) -> Option<TypedPathBuf> {
+    let rest = remainder(path, prefix)?;
+    if rest.is_empty() {
+        return Some(join_components([&b"."[..]], path_type(path)));
+    }
+    Some(join_components(rest, path_type(path)))
+}
+
+/// Move `path` from under the root `old` to under the root `new`.  The rest
+/// of the path is converted when the roots are of different types.
+/// Returns `None` when `path` isn't under `old`.
+pub fn rebase(path: &TypedPath, old: &TypedPath, new: &TypedPath) -> Option<TypedPathBuf> {
+    let rest = join_components(remainder(path, old)?, path_type(path));
+    let rest = match path_type(new) {
+        PathType::Unix => rest.with_unix_encoding(),
+        PathType::Windows => rest.with_windows_encoding(),
+    };
+    Some(new.join(rest.as_bytes()))
+}
@@ -0,0 +1,90 @@
+use crate::component::{arg_into_component, Component};
+use typed_path::TypedPath;
+
+/// A path template like `{parent}/{stem}_thumb.{ext|png}` that is filled
+/// in with components of another path.  Literal braces are written `{{`
+/// and `}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(Vec<u8>),
+    Placeholder {
+        component: Component,
+        /// Used when the component doesn't exist
+        default: Vec<u8>,
+    },
+}
+
+impl Template {
+    pub fn parse(template: &[u8]) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut literal = Vec::new();
+        let mut bytes = template.iter().copied().peekable();
+
+        while let Some(b) = bytes.next() {
+            match b {
+                b'{' if bytes.peek() == Some(&b'{') => {
+                    bytes.next();
+                    literal.push(b'{');
+                }
+                b'}' if bytes.peek() == Some(&b'}') => {
+                    bytes.next();
+                    literal.push(b'}');
+                }
+                b'{' => {
+                    let mut inside = Vec::new();
+                    loop {
+                        match bytes.next() {
+                            Some(b'}') => break,
+                            Some(b) => inside.push(b),
+                            None => return Err("unclosed '{' in template".to_string()),
+                        }
+                    }
+
+                    let (name, default) = match inside.iter().position(|b| *b == b'|') {
+                        Some(i) => (&inside[..i], inside[i + 1..].to_vec()),
+                        None => (&inside[..], Vec::new()),
+                    };
+                    let name = std::str::from_utf8(name)
+                        .map_err(|_| "invalid component in template".to_string())?;
+                    let component = arg_into_component(name)
+                        .map_err(|e| format!("{e} '{name}' in template"))?;
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Piece::Placeholder { component, default });
+                }
+                b'}' => return Err("unmatched '}' in template, use '}}' for a brace".to_string()),
+                b => literal.push(b),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Template { pieces })
+    }
+
+    pub fn render(&self, path: &TypedPath) -> Vec<u8> {
+        let mut rendered = Vec::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(bytes) => rendered.extend_from_slice(bytes),
+                Piece::Placeholder { component, default } => {
+                    let value = component.get(path);
+                    if value.is_empty() {
+                        rendered.extend_from_slice(default);
+                    } else {
+                        rendered.extend_from_slice(&value);
+                    }
+                }
+            }
+        }
+        rendered
+    }
+}
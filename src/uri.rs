@@ -0,0 +1,772 @@
+use crate::component::{nth_index, Component};
+use crate::error::PathmutError;
+use crate::Action;
+use typed_path::Component as _;
+use typed_path::{PathType, TypedPath, TypedPathBuf, WindowsComponent, WindowsPrefix};
+
+/// Bytes that are left as they are in the path of a URI
+fn is_path_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/".contains(&b)
+}
+
+/// Replace every byte that can't be in the path of a URI with `%XX`
+pub fn percent_encode(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len());
+    for b in bytes {
+        if is_path_byte(*b) {
+            encoded.push(*b);
+        } else {
+            encoded.extend_from_slice(format!("%{b:02X}").as_bytes());
+        }
+    }
+    encoded
+}
+
+/// Replace every `%XX` with the byte it stands for, leaving a `%` that
+/// isn't followed by two hex digits as it is
+pub fn percent_decode(bytes: &[u8]) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i..] {
+            [b'%', high, low, ..] => hex(high).zip(hex(low)),
+            _ => None,
+        };
+        match escaped {
+            Some((high, low)) => {
+                decoded.push(high << 4 | low);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Bytes that are left as they are in a query key or value
+fn is_query_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$'()*,;:@/?".contains(&b)
+}
+
+/// Replace every byte that would end a query key or value with `%XX`
+pub fn encode_query(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len());
+    for b in bytes {
+        if is_query_byte(*b) {
+            encoded.push(*b);
+        } else {
+            encoded.extend_from_slice(format!("%{b:02X}").as_bytes());
+        }
+    }
+    encoded
+}
+
+/// Decode a query key or value, where `+` is a space as in forms
+pub fn decode_query(bytes: &[u8]) -> Vec<u8> {
+    let spaced: Vec<u8> = bytes
+        .iter()
+        .map(|b| if *b == b'+' { b' ' } else { *b })
+        .collect();
+    percent_decode(&spaced)
+}
+
+fn param_key(param: &[u8]) -> &[u8] {
+    param.split(|b| *b == b'=').next().unwrap_or(param)
+}
+
+fn param_value(param: &[u8]) -> &[u8] {
+    match param.iter().position(|b| *b == b'=') {
+        Some(i) => &param[i + 1..],
+        None => b"",
+    }
+}
+
+/// Whether the string is a `file:` URI rather than a path.  The scheme
+/// has to be followed by a `/`, since `file:name` is a valid file name.
+pub fn is_file_uri(s: &[u8]) -> bool {
+    s.len() >= 6 && s[..6].eq_ignore_ascii_case(b"file:/")
+}
+
+/// Route of a URI or git remote as a unix path, which path components
+/// act on
+pub(crate) fn route_path(route: &[u8]) -> TypedPath {
+    TypedPath::unix(route)
+}
+
+/// RFC 8089 URI of an absolute path, like `file:///C:/Users/me` for
+/// `C:\Users\me` or `file://server/share` for `\\server\share`.  Returns
+/// `None` for relative paths and windows device paths.
+pub fn to_file_uri(path: &TypedPath) -> Option<Vec<u8>> {
+    if !path.is_absolute() {
+        return None;
+    }
+
+    let mut uri = b"file://".to_vec();
+    let mut names = Vec::new();
+    match path {
+        TypedPath::Unix(unix) => {
+            names.extend(unix.components().skip(1).map(|c| c.as_bytes()));
+        }
+        TypedPath::Windows(windows) => {
+            let mut components = windows.components();
+            let Some(WindowsComponent::Prefix(prefix)) = components.next() else {
+                return None;
+            };
+            match prefix.kind() {
+                WindowsPrefix::Disk(disk) | WindowsPrefix::VerbatimDisk(disk) => {
+                    uri.extend_from_slice(&[b'/', disk, b':']);
+                }
+                WindowsPrefix::UNC(server, share) | WindowsPrefix::VerbatimUNC(server, share) => {
+                    uri.extend_from_slice(&percent_encode(server));
+                    names.push(share);
+                }
+                WindowsPrefix::Verbatim(_) | WindowsPrefix::DeviceNS(_) => return None,
+            }
+            names.extend(components.filter(|c| !c.is_root()).map(|c| c.as_bytes()));
+        }
+    }
+
+    if names.is_empty() {
+        uri.push(b'/');
+    }
+    for name in names {
+        uri.push(b'/');
+        uri.extend_from_slice(&percent_encode(name));
+    }
+    Some(uri)
+}
+
+/// Path of a `file:` URI, which is a windows path when it has a disk like
+/// `file:///C:/` or a server like `file://server/share`, and otherwise a
+/// unix path.  A path type forces the path to be read as that type.
+pub fn from_file_uri(uri: &[u8], path_type: Option<PathType>) -> Option<TypedPathBuf> {
+    if !is_file_uri(uri) {
+        return None;
+    }
+    let rest = &uri[5..];
+    let end = rest
+        .iter()
+        .position(|b| *b == b'?' || *b == b'#')
+        .unwrap_or(rest.len());
+    let rest = &rest[..end];
+
+    let (host, route) = match rest.strip_prefix(b"//") {
+        Some(rest) => {
+            let slash = rest.iter().position(|b| *b == b'/').unwrap_or(rest.len());
+            rest.split_at(slash)
+        }
+        None => (&b""[..], rest),
+    };
+    let host = percent_decode(host);
+    let route = percent_decode(route);
+    let local = host.is_empty() || host.eq_ignore_ascii_case(b"localhost");
+
+    // a disk may be written C: or C| and may be missing the leading slash
+    let unslashed = route.strip_prefix(b"/").unwrap_or(&route);
+    let has_disk = matches!(
+        unslashed,
+        [letter, b':' | b'|', ..] if letter.is_ascii_alphabetic()
+            && matches!(unslashed.get(2), None | Some(b'/'))
+    );
+
+    let windows = match path_type {
+        Some(path_type) => matches!(path_type, PathType::Windows),
+        None => has_disk || !local,
+    };
+    if !windows {
+        let mut unix = Vec::new();
+        if !local {
+            unix.extend_from_slice(b"//");
+            unix.extend_from_slice(&host);
+        }
+        unix.extend_from_slice(&route);
+        return Some(TypedPathBuf::from_unix(unix));
+    }
+
+    let mut path = Vec::new();
+    if !local {
+        path.extend_from_slice(b"\\\\");
+        path.extend_from_slice(&host);
+        path.extend_from_slice(&route);
+    } else if has_disk {
+        path.extend_from_slice(&unslashed[..1]);
+        path.push(b':');
+        path.extend_from_slice(&unslashed[2..]);
+        if path.len() == 2 {
+            path.push(b'/');
+        }
+    } else {
+        path.extend_from_slice(&route);
+    }
+    for b in path.iter_mut().filter(|b| **b == b'/') {
+        *b = b'\\';
+    }
+    Some(TypedPathBuf::from_windows(path))
+}
+
+/// Server part of a URI, written `user:password@host:port`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Authority {
+    pub user: Option<Vec<u8>>,
+    pub password: Option<Vec<u8>>,
+    pub host: Vec<u8>,
+    pub port: Option<Vec<u8>>,
+}
+
+/// URI or relative reference split into its parts by RFC 3986, without
+/// decoding any of them.  Parts that are missing are `None`, so that an
+/// empty query like `x?` is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Uri {
+    pub scheme: Option<Vec<u8>>,
+    pub authority: Option<Authority>,
+    pub route: Vec<u8>,
+    pub query: Option<Vec<u8>>,
+    pub fragment: Option<Vec<u8>>,
+}
+
+impl Authority {
+    pub fn parse(authority: &[u8]) -> Self {
+        let (userinfo, hostport) = match authority.iter().rposition(|b| *b == b'@') {
+            Some(i) => (Some(&authority[..i]), &authority[i + 1..]),
+            None => (None, authority),
+        };
+        let (user, password) = match userinfo {
+            Some(userinfo) => match userinfo.iter().position(|b| *b == b':') {
+                Some(i) => (Some(&userinfo[..i]), Some(&userinfo[i + 1..])),
+                None => (Some(userinfo), None),
+            },
+            None => (None, None),
+        };
+
+        // the colons of an ip v6 host are inside brackets
+        let host_end = hostport.iter().rposition(|b| *b == b']').unwrap_or(0);
+        let (host, port) = match hostport[host_end..].iter().position(|b| *b == b':') {
+            Some(i) => (
+                &hostport[..host_end + i],
+                Some(&hostport[host_end + i + 1..]),
+            ),
+            None => (hostport, None),
+        };
+
+        Authority {
+            user: user.map(<[u8]>::to_vec),
+            password: password.map(<[u8]>::to_vec),
+            host: host.to_vec(),
+            port: port.map(<[u8]>::to_vec),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if let Some(user) = &self.user {
+            bytes.extend_from_slice(user);
+            if let Some(password) = &self.password {
+                bytes.push(b':');
+                bytes.extend_from_slice(password);
+            }
+            bytes.push(b'@');
+        }
+        bytes.extend_from_slice(&self.host);
+        if let Some(port) = &self.port {
+            bytes.push(b':');
+            bytes.extend_from_slice(port);
+        }
+        bytes
+    }
+}
+
+impl Uri {
+    /// Split a URI into its parts.  Anything can be read as a URI, since
+    /// a string without a scheme is a relative reference.
+    pub fn parse(uri: &[u8]) -> Self {
+        let mut rest = uri;
+
+        let scheme_end = rest
+            .iter()
+            .position(|b| !(b.is_ascii_alphanumeric() || b"+-.".contains(b)));
+        let scheme = match scheme_end {
+            Some(i) if i > 0 && rest[i] == b':' && rest[0].is_ascii_alphabetic() => {
+                let scheme = &rest[..i];
+                rest = &rest[i + 1..];
+                Some(scheme.to_vec())
+            }
+            _ => None,
+        };
+
+        let split_off = |rest: &mut &[u8], marker: u8| {
+            let i = rest.iter().position(|b| *b == marker)?;
+            let part = rest[i + 1..].to_vec();
+            *rest = &rest[..i];
+            Some(part)
+        };
+        let fragment = split_off(&mut rest, b'#');
+        let query = split_off(&mut rest, b'?');
+
+        let authority = match rest.strip_prefix(b"//") {
+            Some(after) => {
+                let end = after.iter().position(|b| *b == b'/').unwrap_or(after.len());
+                rest = &after[end..];
+                Some(Authority::parse(&after[..end]))
+            }
+            None => None,
+        };
+
+        Uri {
+            scheme,
+            authority,
+            route: rest.to_vec(),
+            query,
+            fragment,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if let Some(scheme) = &self.scheme {
+            bytes.extend_from_slice(scheme);
+            bytes.push(b':');
+        }
+        if let Some(authority) = &self.authority {
+            bytes.extend_from_slice(b"//");
+            bytes.extend_from_slice(&authority.to_bytes());
+        }
+        bytes.extend_from_slice(&self.route);
+        if let Some(query) = &self.query {
+            bytes.push(b'?');
+            bytes.extend_from_slice(query);
+        }
+        if let Some(fragment) = &self.fragment {
+            bytes.push(b'#');
+            bytes.extend_from_slice(fragment);
+        }
+        bytes
+    }
+
+    pub fn route_path(&self) -> TypedPath {
+        route_path(&self.route)
+    }
+
+    /// Change the route, which has to start with `/` after a server
+    pub fn set_route(&mut self, route: Vec<u8>) {
+        self.route = route;
+        if self.authority.is_some() && !self.route.is_empty() && !self.route.starts_with(b"/") {
+            self.route.insert(0, b'/');
+        }
+    }
+
+    /// Parameters of the query in order, as written
+    pub fn params(&self) -> Vec<&[u8]> {
+        match &self.query {
+            Some(query) => query.split(|b| *b == b'&').collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Change the parameters, removing the query when there are none
+    pub fn set_params(&mut self, params: Vec<Vec<u8>>) {
+        self.query = if params.is_empty() {
+            None
+        } else {
+            Some(params.join(&b'&'))
+        };
+    }
+
+    /// Positions of the parameters with the key, or only of its nth repeat.
+    /// Keys are compared after decoding.
+    fn param_positions(&self, key: &[u8], n: Option<isize>) -> Vec<usize> {
+        let positions: Vec<usize> = self
+            .params()
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| !param.is_empty() && decode_query(param_key(param)) == key)
+            .map(|(i, _)| i)
+            .collect();
+        match n {
+            Some(n) => nth_index(n, positions.len())
+                .and_then(|i| positions.get(i).copied())
+                .into_iter()
+                .collect(),
+            None => positions,
+        }
+    }
+
+    /// Every distinct key of the query, decoded like the keys of
+    /// `query:<key>` are, joined by `&`
+    fn query_keys(&self) -> Vec<u8> {
+        let mut keys: Vec<Vec<u8>> = Vec::new();
+        for key in self
+            .params()
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .map(|p| decode_query(param_key(p)))
+        {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys.join(&b'&')
+    }
+
+    fn authority_mut(&mut self) -> &mut Authority {
+        self.authority.get_or_insert_with(Authority::default)
+    }
+}
+
+impl Component {
+    /// Whether the component is a part of a URI rather than of a path
+    pub fn is_uri_part(&self) -> bool {
+        use Component::*;
+        matches!(
+            self,
+            Scheme
+                | User
+                | Password
+                | Host
+                | Port
+                | Route
+                | Query
+                | QueryParam(..)
+                | QueryKeys
+                | Fragment
+        )
+    }
+
+    pub fn action_uri(&self, action: &Action, uri: &Uri) -> Vec<u8> {
+        match action {
+            Action::Get => self.get_uri(uri),
+            Action::Set(s) => self.set_uri(uri, s),
+            Action::Replace(s) => self.replace_uri(uri, s),
+            Action::Delete => self.delete_uri(uri),
+        }
+    }
+
+    /// Like [`Component::action_uri`], but fails when getting or setting a
+    /// component URIs don't have, a query parameter the query doesn't have,
+    /// or the nth component or repeat past the end
+    pub fn try_action_uri(&self, action: &Action, uri: &Uri) -> Result<Vec<u8>, PathmutError> {
+        match self {
+            // a missing parameter is only added when setting every repeat
+            Component::QueryParam(key, n) if matches!(action, Action::Get | Action::Set(_)) => {
+                if uri.param_positions(key, None).is_empty() {
+                    if n.is_some() || matches!(action, Action::Get) {
+                        return Err(PathmutError::NotApplicable);
+                    }
+                } else if uri.param_positions(key, *n).is_empty() {
+                    return Err(PathmutError::OutOfRange(self.clone()));
+                }
+            }
+            _ if !self.is_uri_part() => self.check_route(action, &uri.route_path())?,
+            _ => {}
+        }
+        Ok(self.action_uri(action, uri))
+    }
+
+    /// Part of the URI, where path components act on the route and
+    /// windows disks are never found
+    pub fn get_uri(&self, uri: &Uri) -> Vec<u8> {
+        use Component::*;
+        let authority = uri.authority.as_ref();
+        let part = match self {
+            Scheme => uri.scheme.as_ref(),
+            User => authority.and_then(|a| a.user.as_ref()),
+            Password => authority.and_then(|a| a.password.as_ref()),
+            Host => authority.map(|a| &a.host),
+            Port => authority.and_then(|a| a.port.as_ref()),
+            Route => Some(&uri.route),
+            Query => uri.query.as_ref(),
+            Fragment => uri.fragment.as_ref(),
+            QueryParam(key, n) => {
+                let params = uri.params();
+                return match uri.param_positions(key, *n).first() {
+                    Some(i) => decode_query(param_value(params[*i])),
+                    None => Vec::new(),
+                };
+            }
+            QueryKeys => return uri.query_keys(),
+            Owner | Repo | Disk => None,
+            path_component => return path_component.get(&uri.route_path()),
+        };
+        part.cloned().unwrap_or_default()
+    }
+
+    pub fn has_uri(&self, uri: &Uri) -> bool {
+        match self {
+            // a parameter can be there without a value, like ?debug
+            Component::QueryParam(key, n) => !uri.param_positions(key, *n).is_empty(),
+            _ => !self.get_uri(uri).is_empty(),
+        }
+    }
+
+    pub fn set_uri(&self, uri: &Uri, value: &[u8]) -> Vec<u8> {
+        use Component::*;
+        let mut uri = uri.clone();
+        let value = value.to_vec();
+        match self {
+            Scheme => uri.scheme = Some(value),
+            User => uri.authority_mut().user = Some(value),
+            Password => {
+                let authority = uri.authority_mut();
+                authority.user.get_or_insert_with(Vec::new);
+                authority.password = Some(value);
+            }
+            Host => {
+                uri.authority_mut().host = value;
+                let route = std::mem::take(&mut uri.route);
+                uri.set_route(route);
+            }
+            Port => uri.authority_mut().port = Some(value),
+            Route => uri.set_route(value),
+            Query => uri.query = Some(value),
+            Fragment => uri.fragment = Some(value),
+            QueryParam(key, n) => {
+                let positions = uri.param_positions(key, *n);
+                let param = [encode_query(key), b"=".to_vec(), encode_query(&value)].concat();
+                let mut params: Vec<Vec<u8>> = uri.params().iter().map(|p| p.to_vec()).collect();
+                match positions.split_first() {
+                    // the first is set and any repeats are removed
+                    Some((first, repeats)) => {
+                        params[*first] = param;
+                        for i in repeats.iter().rev() {
+                            params.remove(*i);
+                        }
+                    }
+                    None if n.is_none() => {
+                        params.retain(|p| !p.is_empty());
+                        params.push(param);
+                    }
+                    None => {}
+                }
+                uri.set_params(params);
+            }
+            QueryKeys | Owner | Repo | Disk => {}
+            path_component => {
+                let route = path_component.set(&uri.route_path(), &value);
+                uri.set_route(route);
+            }
+        }
+        uri.to_bytes()
+    }
+
+    pub fn replace_uri(&self, uri: &Uri, value: &[u8]) -> Vec<u8> {
+        if self.has_uri(uri) {
+            self.set_uri(uri, value)
+        } else {
+            uri.to_bytes()
+        }
+    }
+
+    pub fn delete_uri(&self, uri: &Uri) -> Vec<u8> {
+        use Component::*;
+        let mut uri = uri.clone();
+        let authority = uri.authority.as_mut();
+        match self {
+            Scheme => uri.scheme = None,
+            User => {
+                if let Some(authority) = authority {
+                    authority.user = None;
+                    authority.password = None;
+                }
+            }
+            Password => {
+                if let Some(authority) = authority {
+                    authority.password = None;
+                }
+            }
+            Host => {
+                if let Some(authority) = authority {
+                    authority.host.clear();
+                }
+            }
+            Port => {
+                if let Some(authority) = authority {
+                    authority.port = None;
+                }
+            }
+            Route => uri.route.clear(),
+            Query => uri.query = None,
+            Fragment => uri.fragment = None,
+            QueryParam(key, n) => {
+                let mut params: Vec<Vec<u8>> = uri.params().iter().map(|p| p.to_vec()).collect();
+                for i in uri.param_positions(key, *n).iter().rev() {
+                    params.remove(*i);
+                }
+                uri.set_params(params);
+            }
+            QueryKeys | Owner | Repo | Disk => {}
+            path_component => {
+                let route = path_component.delete(&uri.route_path());
+                uri.set_route(route);
+            }
+        }
+        uri.to_bytes()
+    }
+}
+
+/// Port a scheme uses when none is given
+fn default_port(scheme: &[u8]) -> Option<&'static [u8]> {
+    match scheme {
+        b"http" | b"ws" => Some(b"80"),
+        b"https" | b"wss" => Some(b"443"),
+        b"ftp" => Some(b"21"),
+        _ => None,
+    }
+}
+
+/// Decode escaped unreserved characters and write the hex digits of the
+/// other escapes in uppercase, so equal URIs are written the same
+fn normalize_escapes(bytes: &[u8]) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i..] {
+            [b'%', high, low, ..] => hex(high).zip(hex(low)).map(|(h, l)| h << 4 | l),
+            _ => None,
+        };
+        match escaped {
+            Some(b) if b.is_ascii_alphanumeric() || b"-._~".contains(&b) => normalized.push(b),
+            Some(b) => normalized.extend_from_slice(format!("%{b:02X}").as_bytes()),
+            None => normalized.push(bytes[i]),
+        }
+        i += if escaped.is_some() { 3 } else { 1 };
+    }
+    normalized
+}
+
+/// Resolve the `.` and `..` segments of a URI path, following RFC 3986
+/// section 5.2.4
+pub fn remove_dot_segments(path: &[u8]) -> Vec<u8> {
+    let mut input = path;
+    let mut output: Vec<u8> = Vec::with_capacity(path.len());
+    let pop_segment = |output: &mut Vec<u8>| {
+        let last = output.iter().rposition(|b| *b == b'/').unwrap_or(0);
+        output.truncate(last);
+    };
+
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix(b"../") {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix(b"./") {
+            input = rest;
+        } else if input.starts_with(b"/./") {
+            input = &input[2..];
+        } else if input == b"/." {
+            input = b"/";
+        } else if input.starts_with(b"/../") {
+            input = &input[3..];
+            pop_segment(&mut output);
+        } else if input == b"/.." {
+            input = b"/";
+            pop_segment(&mut output);
+        } else if input == b"." || input == b".." {
+            input = b"";
+        } else {
+            let start = input.starts_with(b"/") as usize;
+            let end = input[start..]
+                .iter()
+                .position(|b| *b == b'/')
+                .map_or(input.len(), |i| i + start);
+            output.extend_from_slice(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+impl Uri {
+    /// Equivalent URI written the usual way, following RFC 3986 section 6.
+    /// The scheme and host are lowercased, default ports dropped, dot
+    /// segments removed, and escapes normalized.
+    pub fn normalize(&self) -> Uri {
+        let mut uri = self.clone();
+        if let Some(scheme) = &mut uri.scheme {
+            scheme.make_ascii_lowercase();
+        }
+        if let Some(authority) = &mut uri.authority {
+            for part in [&mut authority.user, &mut authority.password]
+                .into_iter()
+                .flatten()
+            {
+                *part = normalize_escapes(part);
+            }
+            authority.host = normalize_escapes(&authority.host.to_ascii_lowercase());
+
+            let default = uri.scheme.as_deref().and_then(default_port);
+            if authority
+                .port
+                .as_deref()
+                .is_some_and(|port| port.is_empty())
+                || authority.port.as_deref() == default
+            {
+                authority.port = None;
+            }
+            if uri.route.is_empty() {
+                uri.route = b"/".to_vec();
+            }
+        }
+        // dot segments of a relative reference only mean something once
+        // it is resolved
+        uri.route = normalize_escapes(&uri.route);
+        if uri.scheme.is_some() || uri.authority.is_some() || uri.route.starts_with(b"/") {
+            uri.route = remove_dot_segments(&uri.route);
+        }
+        for part in [&mut uri.query, &mut uri.fragment].into_iter().flatten() {
+            *part = normalize_escapes(part);
+        }
+        uri
+    }
+
+    /// Target of a reference relative to this URI, following RFC 3986
+    /// section 5.2.2
+    pub fn resolve(&self, reference: &Uri) -> Uri {
+        let mut target = Uri {
+            fragment: reference.fragment.clone(),
+            ..Uri::default()
+        };
+        if reference.scheme.is_some() {
+            target.scheme = reference.scheme.clone();
+            target.authority = reference.authority.clone();
+            target.route = remove_dot_segments(&reference.route);
+            target.query = reference.query.clone();
+            return target;
+        }
+
+        target.scheme = self.scheme.clone();
+        if reference.authority.is_some() {
+            target.authority = reference.authority.clone();
+            target.route = remove_dot_segments(&reference.route);
+            target.query = reference.query.clone();
+            return target;
+        }
+
+        target.authority = self.authority.clone();
+        if reference.route.is_empty() {
+            target.route = self.route.clone();
+            target.query = reference.query.clone().or_else(|| self.query.clone());
+        } else {
+            target.route = if reference.route.starts_with(b"/") {
+                remove_dot_segments(&reference.route)
+            } else {
+                remove_dot_segments(&self.merge(&reference.route))
+            };
+            target.query = reference.query.clone();
+        }
+        target
+    }
+
+    /// Relative path appended to the directory of this URI's route
+    fn merge(&self, route: &[u8]) -> Vec<u8> {
+        if self.authority.is_some() && self.route.is_empty() {
+            return [b"/", route].concat();
+        }
+        match self.route.iter().rposition(|b| *b == b'/') {
+            Some(i) => [&self.route[..=i], route].concat(),
+            None => route.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+
+use typed_path::{PathType, TypedPath};
+
+/// Platform whose rules decide if a path could exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Unix,
+    Windows,
+    MacOs,
+    /// Valid on every other platform
+    Portable,
+}
+
+impl From<PathType> for Platform {
+    fn from(path_type: PathType) -> Self {
+        match path_type {
+            PathType::Unix => Platform::Unix,
+            PathType::Windows => Platform::Windows,
+        }
+    }
+}
+
+impl Platform {
+    fn windows_rules(self) -> bool {
+        matches!(self, Platform::Windows | Platform::Portable)
+    }
+
+    /// Whether the byte can't be in a file name
+    pub fn forbids(self, b: u8) -> bool {
+        match self {
+            _ if b == 0 => true,
+            Platform::Unix => b == b'/',
+            Platform::MacOs => b == b':' || b == b'/',
+            Platform::Windows | Platform::Portable => b < 0x20 || b"<>:\"|?*\\/".contains(&b),
+        }
+    }
+
+    pub fn max_component_len(self) -> usize {
+        255
+    }
+
+    pub fn max_path_len(self) -> usize {
+        match self {
+            Platform::Unix => 4096,
+            Platform::MacOs => 1024,
+            Platform::Windows | Platform::Portable => 260,
+        }
+    }
+}
+
+/// Rule a path breaks on some platform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invalid {
+    Nul,
+    ForbiddenCharacter(u8),
+    ReservedName(Vec<u8>),
+    TrailingDotOrSpace,
+    ComponentTooLong(usize),
+    PathTooLong(usize),
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Invalid::Nul => write!(f, "contains a NUL byte"),
+            Invalid::ForbiddenCharacter(b) => {
+                write!(f, "contains forbidden character '{}'", b.escape_ascii())
+            }
+            Invalid::ReservedName(name) => {
+                write!(f, "uses reserved name '{}'", String::from_utf8_lossy(name))
+            }
+            Invalid::TrailingDotOrSpace => write!(f, "has a name ending in a dot or space"),
+            Invalid::ComponentTooLong(max) => {
+                write!(f, "has a name longer than {max} bytes")
+            }
+            Invalid::PathTooLong(max) => write!(f, "is longer than {max} bytes"),
+        }
+    }
+}
+
+/// Device names windows reserves in every directory, with or without an
+/// extension
+pub const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Whether windows treats the file name as a device, like `con` or `NUL.txt`
+pub fn is_reserved_name(name: &[u8]) -> bool {
+    let stem = name.split(|b| *b == b'.').next().unwrap_or(name);
+    let stem = stem.trim_ascii_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved.as_bytes()))
+}
+
+/// Every rule the path breaks on the platform, in the order the rules are
+/// checked.  Only file names are checked for characters and reserved
+/// names, so a disk like `C:` is fine anywhere.
+pub fn invalid_reasons(path: &TypedPath, platform: Platform) -> Vec<Invalid> {
+    let mut reasons = Vec::new();
+    let mut add = |reason: Invalid| {
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
+        }
+    };
+
+    if path.as_bytes().contains(&0) {
+        add(Invalid::Nul);
+    }
+    for name in path.components().filter(|c| c.is_normal()) {
+        let name = name.as_bytes();
+        for b in name.iter().filter(|b| **b != 0 && platform.forbids(**b)) {
+            add(Invalid::ForbiddenCharacter(*b));
+        }
+        if platform.windows_rules() {
+            if is_reserved_name(name) {
+                add(Invalid::ReservedName(name.to_vec()));
+            }
+            if name.ends_with(b".") || name.ends_with(b" ") {
+                add(Invalid::TrailingDotOrSpace);
+            }
+        }
+        if name.len() > platform.max_component_len() {
+            add(Invalid::ComponentTooLong(platform.max_component_len()));
+        }
+    }
+    if path.as_bytes().len() > platform.max_path_len() {
+        add(Invalid::PathTooLong(platform.max_path_len()));
+    }
+    reasons
+}
+
+/// Whether the path could exist on the platform
+pub fn is_valid(path: &TypedPath, platform: Platform) -> bool {
+    invalid_reasons(path, platform).is_empty()
+}
+
+/// Turns any string into a file name that is valid on a platform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sanitizer {
+    pub platform: Platform,
+    /// Put in place of each forbidden character, or nothing to remove them
+    pub replacement: Vec<u8>,
+    /// Length in bytes the name is cut down to, keeping its extension
+    pub max_len: usize,
+}
+
+impl Sanitizer {
+    pub fn new(platform: Platform) -> Self {
+        Sanitizer {
+            platform,
+            replacement: b"_".to_vec(),
+            max_len: platform.max_component_len(),
+        }
+    }
+
+    /// Replacement for a whole name, which can't be left empty
+    fn filler(&self) -> &[u8] {
+        if self.replacement.is_empty() {
+            b"_"
+        } else {
+            &self.replacement
+        }
+    }
+
+    fn trim_end<'a>(&self, name: &'a [u8]) -> &'a [u8] {
+        if !self.platform.windows_rules() {
+            return name;
+        }
+        let end = name
+            .iter()
+            .rposition(|b| *b != b'.' && *b != b' ')
+            .map_or(0, |i| i + 1);
+        &name[..end]
+    }
+
+    pub fn sanitize(&self, name: &[u8]) -> Vec<u8> {
+        let mut sanitized = Vec::with_capacity(name.len());
+        for b in name {
+            if self.platform.forbids(*b) {
+                sanitized.extend_from_slice(&self.replacement);
+            } else {
+                sanitized.push(*b);
+            }
+        }
+        let mut sanitized = self.trim_end(&sanitized).to_vec();
+
+        // the name is a device with or without an extension
+        if self.platform.windows_rules() && is_reserved_name(&sanitized) {
+            let end = sanitized
+                .iter()
+                .position(|b| *b == b'.')
+                .unwrap_or(sanitized.len());
+            sanitized.splice(end..end, self.filler().iter().copied());
+        }
+
+        if sanitized.len() > self.max_len {
+            let ext = match sanitized.iter().rposition(|b| *b == b'.') {
+                Some(i) if i > 0 && sanitized.len() - i < self.max_len => sanitized.split_off(i),
+                _ => Vec::new(),
+            };
+            let mut end = self.max_len - ext.len();
+            // don't cut a character in half
+            while end > 0 && sanitized[end] & 0xc0 == 0x80 {
+                end -= 1;
+            }
+            sanitized.truncate(end);
+            sanitized.truncate(self.trim_end(&sanitized).len());
+            sanitized.extend(ext);
+        }
+
+        if sanitized.is_empty() || sanitized == b"." || sanitized == b".." {
+            sanitized = self.filler().to_vec();
+        }
+        sanitized
+    }
+}